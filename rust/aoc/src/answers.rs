@@ -0,0 +1,238 @@
+//! Local store of submitted answers and the feedback they received.
+//!
+//! Each day keeps an `answers.toml` next to its `Cargo.toml`:
+//!
+//! ```toml
+//! [part-1]
+//! correct = 2164
+//!
+//! [part-2]
+//! too-high = [70000]
+//! too-low = [60000]
+//! wrong = [65432]
+//! ```
+//!
+//! After a run the computed answers are compared against this file, so a refactor
+//! can be checked against known-good values without submitting anything.
+//! `aoc answer <day> <part> <value> <feedback>` records the feedback a submission got.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+#[derive(Serialize, Deserialize, Default, PartialEq, Debug, Clone)]
+pub struct AnswerStore {
+    #[serde(flatten)]
+    parts: BTreeMap<String, PartAnswers>,
+}
+
+#[derive(Serialize, Deserialize, Default, PartialEq, Debug, Clone)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct PartAnswers {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub correct: Option<u64>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub too_high: Vec<u64>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub too_low: Vec<u64>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub wrong: Vec<u64>,
+}
+
+/// Feedback received after submitting an answer.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum Feedback {
+    Correct,
+    Wrong,
+    TooHigh,
+    TooLow,
+}
+
+/// Outcome of comparing a computed answer against the store.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum Verdict {
+    Correct,
+    Wrong,
+    TooHigh,
+    TooLow,
+    Unknown,
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            Verdict::Correct => "correct",
+            Verdict::Wrong => "wrong",
+            Verdict::TooHigh => "too high",
+            Verdict::TooLow => "too low",
+            Verdict::Unknown => "unverified",
+        };
+        return write!(f, "{}", text);
+    }
+}
+
+impl AnswerStore {
+    /// Loads the store from `path`. A missing file is treated as an empty store.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<AnswerStore> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(AnswerStore::default()),
+            Err(error) => return Err(error),
+        };
+        return toml::from_str(&content)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error));
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let content = toml::to_string(self)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        return fs::write(path, content);
+    }
+
+    pub fn part(&self, part: u8) -> Option<&PartAnswers> {
+        return self.parts.get(&part_key(part));
+    }
+
+    /// Stores the feedback received for `value`, keeping each value at most once per list.
+    pub fn record(&mut self, part: u8, value: u64, feedback: Feedback) {
+        let answers = self.parts.entry(part_key(part)).or_default();
+        let list = match feedback {
+            Feedback::Correct => {
+                answers.correct = Some(value);
+                return;
+            }
+            Feedback::Wrong => &mut answers.wrong,
+            Feedback::TooHigh => &mut answers.too_high,
+            Feedback::TooLow => &mut answers.too_low,
+        };
+        if !list.contains(&value) {
+            list.push(value);
+            list.sort();
+        }
+    }
+
+    /// Judges `value` against the confirmed answer, or failing that against earlier feedback.
+    pub fn verify(&self, part: u8, value: u64) -> Verdict {
+        let answers = match self.part(part) {
+            Some(answers) => answers,
+            None => return Verdict::Unknown,
+        };
+
+        if let Some(correct) = answers.correct {
+            return if value == correct { Verdict::Correct }
+            else if value > correct { Verdict::TooHigh }
+            else { Verdict::TooLow };
+        }
+        if answers.too_high.iter().any(|&too_high| value >= too_high) {
+            return Verdict::TooHigh;
+        }
+        if answers.too_low.iter().any(|&too_low| value <= too_low) {
+            return Verdict::TooLow;
+        }
+        if answers.wrong.contains(&value) {
+            return Verdict::Wrong;
+        }
+        return Verdict::Unknown;
+    }
+}
+
+/// Parses feedback as it is written in the store: `correct`, `wrong`, `too-high` or `too-low`.
+pub fn parse_feedback(text: &str) -> Result<Feedback, String> {
+    return match text {
+        "correct" => Ok(Feedback::Correct),
+        "wrong" => Ok(Feedback::Wrong),
+        "too-high" => Ok(Feedback::TooHigh),
+        "too-low" => Ok(Feedback::TooLow),
+        _ => Err(format!("unknown feedback {:?}, expected correct, wrong, too-high or too-low", text)),
+    };
+}
+
+fn part_key(part: u8) -> String {
+    return format!("part-{}", part);
+}
+
+/// Prints the verdict for each `(part, answer)` pair against the store at `path`.
+pub fn report<P: AsRef<Path>>(path: P, results: &[(u8, u64)]) {
+    let store = match AnswerStore::load(&path) {
+        Ok(store) => store,
+        Err(error) => {
+            println!("Could not load answers from {}: {}", path.as_ref().display(), error);
+            return;
+        }
+    };
+    for (part, value) in results {
+        println!("Part {} answer {}: {}", part, value, store.verify(*part, *value));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify_unknown_part() {
+        let store = AnswerStore::default();
+        assert_eq!(store.verify(1, 42), Verdict::Unknown);
+    }
+
+    #[test]
+    fn test_verify_against_correct_answer() {
+        let mut store = AnswerStore::default();
+        store.record(1, 42, Feedback::Correct);
+
+        assert_eq!(store.verify(1, 42), Verdict::Correct);
+        assert_eq!(store.verify(1, 43), Verdict::TooHigh);
+        assert_eq!(store.verify(1, 41), Verdict::TooLow);
+    }
+
+    #[test]
+    fn test_verify_against_prior_feedback() {
+        let mut store = AnswerStore::default();
+        store.record(2, 100, Feedback::TooHigh);
+        store.record(2, 10, Feedback::TooLow);
+        store.record(2, 50, Feedback::Wrong);
+
+        assert_eq!(store.verify(2, 120), Verdict::TooHigh);
+        assert_eq!(store.verify(2, 5), Verdict::TooLow);
+        assert_eq!(store.verify(2, 50), Verdict::Wrong);
+        assert_eq!(store.verify(2, 51), Verdict::Unknown);
+    }
+
+    #[test]
+    fn test_parse_answers_toml() {
+        let store: AnswerStore = toml::from_str(
+            "[part-1]\ncorrect = 8\n\n[part-2]\ntoo-high = [3000]\nwrong = [2000]\n"
+        ).unwrap();
+
+        assert_eq!(store.verify(1, 8), Verdict::Correct);
+        assert_eq!(
+            store.part(2),
+            Some(&PartAnswers {
+                correct: None,
+                too_high: vec![3000],
+                too_low: vec![],
+                wrong: vec![2000],
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_feedback() {
+        assert_eq!(parse_feedback("too-high"), Ok(Feedback::TooHigh));
+        assert_eq!(parse_feedback("correct"), Ok(Feedback::Correct));
+        assert!(parse_feedback("too high").is_err());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut store = AnswerStore::default();
+        store.record(1, 8, Feedback::Correct);
+        store.record(2, 3000, Feedback::TooHigh);
+
+        let parsed: AnswerStore = toml::from_str(&toml::to_string(&store).unwrap()).unwrap();
+        assert_eq!(parsed, store);
+    }
+}
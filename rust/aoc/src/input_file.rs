@@ -0,0 +1,233 @@
+//! Loads puzzle inputs into memory and iterates their lines as byte slices.
+
+use crate::normalize::{is_blank, strip_bom};
+use memmap2::{Mmap, MmapOptions};
+use std::error::Error;
+use std::fmt;
+use std::fs::{self, File};
+use std::hint::black_box;
+use std::io;
+use std::path::Path;
+
+const PAGE_SIZE: usize = 4096;
+
+pub struct InputFile {
+    content: Content,
+}
+
+enum Content {
+    Mapped(Mmap),
+    Owned(Vec<u8>),
+}
+
+impl InputFile {
+    /// Memory-maps the file at `path`.
+    ///
+    /// The pages are faulted in before returning, so the time spent here is the real cost
+    /// of loading the file rather than just opening it.
+    pub fn map<P: AsRef<Path>>(path: P) -> io::Result<InputFile> {
+        let file = File::open(path)?;
+        // Empty files cannot be mapped on every platform.
+        if file.metadata()?.len() == 0 {
+            return Ok(InputFile { content: Content::Owned(Vec::new()) });
+        }
+
+        // SAFETY: inputs are not modified while a day is processing them.
+        let mmap = unsafe { MmapOptions::new().populate().map(&file)? };
+        black_box(
+            mmap.iter()
+                .step_by(PAGE_SIZE)
+                .fold(0u8, |acc, byte| acc ^ byte)
+        );
+        return Ok(InputFile { content: Content::Mapped(mmap) });
+    }
+
+    /// Reads the whole file at `path` into an owned buffer.
+    pub fn read<P: AsRef<Path>>(path: P) -> io::Result<InputFile> {
+        return Ok(InputFile { content: Content::Owned(fs::read(path)?) });
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        return match &self.content {
+            Content::Mapped(mmap) => mmap,
+            Content::Owned(bytes) => bytes,
+        };
+    }
+
+    /// Iterates the lines of the file without their `\n` or `\r\n` terminators,
+    /// matching `BufRead::lines`.
+    pub fn lines(&self) -> ByteLines<'_> {
+        return ByteLines::new(self.as_bytes());
+    }
+
+    /// Hands the file to a [`LineCursor`] positioned after any byte order mark.
+    pub fn into_cursor(self) -> LineCursor {
+        let bytes = self.as_bytes();
+        let offset = bytes.len() - strip_bom(bytes).len();
+        return LineCursor {
+            input: self,
+            offset,
+            line_number: 0,
+        };
+    }
+}
+
+/// Owns an [`InputFile`] and hands out its lines one at a time, for readers that parse lazily.
+pub struct LineCursor {
+    input: InputFile,
+    offset: usize,
+    line_number: usize,
+}
+
+impl LineCursor {
+    /// Returns the next line together with its 1-based line number.
+    pub fn next_line(&mut self) -> Option<(usize, &[u8])> {
+        return self.advance(false);
+    }
+
+    /// Like [`LineCursor::next_line`], skipping blank lines.
+    pub fn next_content_line(&mut self) -> Option<(usize, &[u8])> {
+        return self.advance(true);
+    }
+
+    fn advance(&mut self, skip_blank: bool) -> Option<(usize, &[u8])> {
+        let bytes = &self.input.as_bytes()[self.offset..];
+        let mut lines = ByteLines::new(bytes);
+        while let Some(line) = lines.next() {
+            self.line_number += 1;
+            if !skip_blank || !is_blank(line) {
+                self.offset += bytes.len() - lines.remaining().len();
+                return Some((self.line_number, line));
+            }
+        }
+        self.offset += bytes.len();
+        return None;
+    }
+}
+
+pub struct ByteLines<'a> {
+    remaining: &'a [u8],
+}
+
+impl<'a> ByteLines<'a> {
+    pub fn new(bytes: &'a [u8]) -> ByteLines<'a> {
+        return ByteLines { remaining: bytes };
+    }
+
+    /// The bytes following the last line returned.
+    pub fn remaining(&self) -> &'a [u8] {
+        return self.remaining;
+    }
+}
+
+impl<'a> Iterator for ByteLines<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<&'a [u8]> {
+        if self.remaining.is_empty() {
+            return None;
+        }
+
+        let (line, rest) = match self.remaining.iter().position(|&byte| byte == b'\n') {
+            Some(index) => (&self.remaining[..index], &self.remaining[index + 1..]),
+            None => (self.remaining, &self.remaining[self.remaining.len()..]),
+        };
+        self.remaining = rest;
+        return Some(line.strip_suffix(b"\r").unwrap_or(line));
+    }
+}
+
+/// An error `E` raised by the line with the given 1-based number.
+#[derive(PartialEq, Debug, Clone)]
+pub struct LineError<E> {
+    pub line: usize,
+    pub error: E,
+}
+
+impl<E: fmt::Display> fmt::Display for LineError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return write!(f, "line {}: {}", self.line, self.error);
+    }
+}
+
+impl<E: Error + 'static> Error for LineError<E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        return Some(&self.error);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    fn lines_of(content: &[u8]) -> Vec<&[u8]> {
+        return ByteLines::new(content).collect();
+    }
+
+    #[test]
+    fn test_lines_without_trailing_newline() {
+        assert_eq!(lines_of(b"one\ntwo"), vec![&b"one"[..], &b"two"[..]]);
+    }
+
+    #[test]
+    fn test_lines_with_trailing_newline() {
+        assert_eq!(lines_of(b"one\ntwo\n"), vec![&b"one"[..], &b"two"[..]]);
+    }
+
+    #[test]
+    fn test_lines_strip_carriage_returns() {
+        assert_eq!(lines_of(b"one\r\n\r\ntwo\r\n"), vec![&b"one"[..], &b""[..], &b"two"[..]]);
+    }
+
+    #[test]
+    fn test_map_matches_read() {
+        let path = env::temp_dir().join(format!("aoc-input-file-{}.txt", std::process::id()));
+        fs::write(&path, "1abc2\npqr3stu8vwx\n").unwrap();
+
+        let mapped = InputFile::map(&path).unwrap();
+        let read = InputFile::read(&path).unwrap();
+        assert_eq!(mapped.as_bytes(), read.as_bytes());
+        assert_eq!(mapped.lines().count(), 2);
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_cursor_numbers_lines() {
+        let path = env::temp_dir().join(format!("aoc-input-file-cursor-{}.txt", std::process::id()));
+        fs::write(&path, "one\r\ntwo\n\nthree").unwrap();
+
+        let mut cursor = InputFile::read(&path).unwrap().into_cursor();
+        assert_eq!(cursor.next_line(), Some((1, &b"one"[..])));
+        assert_eq!(cursor.next_line(), Some((2, &b"two"[..])));
+        assert_eq!(cursor.next_line(), Some((3, &b""[..])));
+        assert_eq!(cursor.next_line(), Some((4, &b"three"[..])));
+        assert_eq!(cursor.next_line(), None);
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_cursor_skips_bom_and_blank_lines() {
+        let path = env::temp_dir().join(format!("aoc-input-file-content-{}.txt", std::process::id()));
+        fs::write(&path, "\u{FEFF}one\r\n\r\ntwo\n \n").unwrap();
+
+        let mut cursor = InputFile::read(&path).unwrap().into_cursor();
+        assert_eq!(cursor.next_content_line(), Some((1, &b"one"[..])));
+        assert_eq!(cursor.next_content_line(), Some((3, &b"two"[..])));
+        assert_eq!(cursor.next_content_line(), None);
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_map_empty_file() {
+        let path = env::temp_dir().join(format!("aoc-input-file-empty-{}.txt", std::process::id()));
+        fs::write(&path, "").unwrap();
+
+        assert_eq!(InputFile::map(&path).unwrap().lines().count(), 0);
+
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,187 @@
+//! Fetches puzzle inputs and caches them on disk.
+//!
+//! Inputs are requested from `{base_url}/{year}/day/{day}/input` with the session cookie
+//! and stored under `{cache_dir}/{year}/day-{day}.txt`, so each input is downloaded once.
+//! The base URL can point at a local stand-in server for testing.
+//!
+//! Configuration is read from the environment by [`InputManager::from_env`]:
+//! `AOC_SESSION` (or `~/.config/aoc/session`), `AOC_BASE_URL` and `AOC_CACHE_DIR`.
+
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+pub const DEFAULT_BASE_URL: &str = "https://adventofcode.com";
+pub const DEFAULT_MIN_INTERVAL: Duration = Duration::from_secs(5);
+const USER_AGENT: &str = "github.com/pgerhard/advent-of-code-2023 input fetcher";
+const LAST_REQUEST_FILE: &str = ".last-request";
+
+#[derive(Debug, Clone)]
+pub struct InputManager {
+    pub base_url: String,
+    pub session: Option<String>,
+    pub cache_dir: PathBuf,
+    /// Minimum time between two requests to the server, shared across runs via the cache.
+    pub min_interval: Duration,
+}
+
+impl InputManager {
+    pub fn from_env() -> InputManager {
+        let home = env::var("HOME").map(PathBuf::from).unwrap_or_else(|_| PathBuf::from("."));
+        let session = env::var("AOC_SESSION").ok()
+            .or_else(|| fs::read_to_string(home.join(".config/aoc/session")).ok())
+            .map(|session| session.trim().to_string())
+            .filter(|session| !session.is_empty());
+
+        return InputManager {
+            base_url: env::var("AOC_BASE_URL").unwrap_or_else(|_| DEFAULT_BASE_URL.to_string()),
+            session,
+            cache_dir: env::var("AOC_CACHE_DIR").map(PathBuf::from).unwrap_or_else(|_| home.join(".cache/aoc")),
+            min_interval: DEFAULT_MIN_INTERVAL,
+        };
+    }
+
+    pub fn cache_path(&self, year: u16, day: u8) -> PathBuf {
+        return self.cache_dir.join(year.to_string()).join(format!("day-{}.txt", day));
+    }
+
+    /// Returns the input for `year`/`day`, downloading it only when it is not cached yet.
+    pub fn input(&self, year: u16, day: u8) -> io::Result<String> {
+        let cache_path = self.cache_path(year, day);
+        if let Ok(cached) = fs::read_to_string(&cache_path) {
+            return Ok(cached);
+        }
+
+        let input = self.download(year, day)?;
+        fs::create_dir_all(cache_path.parent().unwrap())?;
+        fs::write(&cache_path, &input)?;
+        return Ok(input);
+    }
+
+    /// Writes the input to `target` unless it already contains something.
+    /// Returns whether the file was written.
+    pub fn provision<P: AsRef<Path>>(&self, year: u16, day: u8, target: P) -> io::Result<bool> {
+        let target = target.as_ref();
+        if fs::metadata(target).map(|metadata| metadata.len() > 0).unwrap_or(false) {
+            return Ok(false);
+        }
+        let input = self.input(year, day)?;
+        fs::write(target, input)?;
+        return Ok(true);
+    }
+
+    fn download(&self, year: u16, day: u8) -> io::Result<String> {
+        let session = self.session.as_ref().ok_or_else(|| io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            "no session cookie configured, set AOC_SESSION",
+        ))?;
+
+        self.wait_for_rate_limit()?;
+        let url = format!("{}/{}/day/{}/input", self.base_url.trim_end_matches('/'), year, day);
+        let mut response = ureq::get(&url)
+            .header("Cookie", &format!("session={}", session))
+            .header("User-Agent", USER_AGENT)
+            .call()
+            .map_err(|error| io::Error::other(format!("fetching {} failed: {}", url, error)))?;
+        return response.body_mut().read_to_string().map_err(|error| error.into_io());
+    }
+
+    fn wait_for_rate_limit(&self) -> io::Result<()> {
+        let marker = self.cache_dir.join(LAST_REQUEST_FILE);
+        let last_request = fs::read_to_string(&marker).ok()
+            .and_then(|millis| millis.trim().parse::<u64>().ok())
+            .map(|millis| UNIX_EPOCH + Duration::from_millis(millis));
+
+        if let Some(last_request) = last_request {
+            let elapsed = SystemTime::now().duration_since(last_request).unwrap_or(Duration::ZERO);
+            if elapsed < self.min_interval {
+                thread::sleep(self.min_interval - elapsed);
+            }
+        }
+
+        fs::create_dir_all(&self.cache_dir)?;
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or(Duration::ZERO);
+        return fs::write(marker, now.as_millis().to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+
+    // Serves a single request and hands back the request head that was received.
+    fn serve_once(body: &'static str) -> (String, thread::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let handle = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = String::new();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line.trim().is_empty() {
+                    break;
+                }
+                request.push_str(&line);
+            }
+            write!(stream, "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", body.len(), body).unwrap();
+            return request;
+        });
+        return (base_url, handle);
+    }
+
+    fn test_manager(base_url: String, name: &str) -> InputManager {
+        let cache_dir = env::temp_dir().join(format!("aoc-inputs-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&cache_dir);
+        return InputManager {
+            base_url,
+            session: Some("secret".to_string()),
+            cache_dir,
+            min_interval: Duration::ZERO,
+        };
+    }
+
+    #[test]
+    fn test_input_is_downloaded_once_and_cached() {
+        let (base_url, server) = serve_once("Game 1: 3 blue\n");
+        let manager = test_manager(base_url, "cache");
+
+        assert_eq!(manager.input(2023, 2).unwrap(), "Game 1: 3 blue\n");
+        let request = server.join().unwrap();
+        assert!(request.starts_with("GET /2023/day/2/input "));
+        assert!(request.contains("session=secret"));
+
+        // The server is gone, so this can only be answered from the cache.
+        assert_eq!(manager.input(2023, 2).unwrap(), "Game 1: 3 blue\n");
+        assert!(manager.cache_path(2023, 2).exists());
+
+        fs::remove_dir_all(&manager.cache_dir).unwrap();
+    }
+
+    #[test]
+    fn test_provision_keeps_existing_input() {
+        let manager = test_manager("http://127.0.0.1:9".to_string(), "provision");
+        fs::create_dir_all(&manager.cache_dir).unwrap();
+        let target = manager.cache_dir.join("input.txt");
+        fs::write(&target, "already here").unwrap();
+
+        assert_eq!(manager.provision(2023, 1, &target).unwrap(), false);
+        assert_eq!(fs::read_to_string(&target).unwrap(), "already here");
+
+        fs::remove_dir_all(&manager.cache_dir).unwrap();
+    }
+
+    #[test]
+    fn test_missing_session_is_reported() {
+        let mut manager = test_manager("http://127.0.0.1:9".to_string(), "session");
+        manager.session = None;
+
+        assert_eq!(manager.input(2023, 1).unwrap_err().kind(), io::ErrorKind::PermissionDenied);
+    }
+}
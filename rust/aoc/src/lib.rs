@@ -0,0 +1,6 @@
+//! Shared building blocks for the Advent of Code day crates.
+
+pub mod answers;
+pub mod input_file;
+pub mod inputs;
+pub mod normalize;
@@ -0,0 +1,91 @@
+//! Smooths over the differences between inputs saved on other machines or edited by hand:
+//! byte order marks, `\r\n` terminators, blank lines and text that is not UTF-8.
+
+use crate::input_file::ByteLines;
+use std::borrow::Cow;
+
+/// The UTF-8 byte order mark some editors write at the start of a file.
+pub const BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// `bytes` without a leading byte order mark.
+pub fn strip_bom(bytes: &[u8]) -> &[u8] {
+    return bytes.strip_prefix(BOM).unwrap_or(bytes);
+}
+
+/// Whether `line` is empty or holds only ASCII whitespace.
+pub fn is_blank(line: &[u8]) -> bool {
+    return line.iter().all(|byte| byte.is_ascii_whitespace());
+}
+
+/// Iterates the lines of `input` that are not blank, each with its 1-based line number.
+///
+/// A leading byte order mark is skipped and terminators are removed as by [`ByteLines`],
+/// so line numbers still match what an editor shows.
+pub fn content_lines(input: &[u8]) -> impl Iterator<Item = (usize, &[u8])> {
+    return ByteLines::new(strip_bom(input))
+        .enumerate()
+        .map(|(index, line)| (index + 1, line))
+        .filter(|(_, line)| !is_blank(line));
+}
+
+/// What to do with bytes that are not valid UTF-8, as found in corrupted or Latin-1 files.
+#[derive(PartialEq, Debug, Clone, Copy, Default)]
+pub enum InvalidUtf8 {
+    /// Lines holding invalid bytes are errors.
+    #[default]
+    Report,
+    /// Every invalid sequence is read as U+FFFD, the replacement character.
+    Replace,
+}
+
+impl InvalidUtf8 {
+    /// `input` as the parsers should see it: unchanged when reporting, so invalid lines are
+    /// still caught where they are parsed, or with invalid sequences replaced. Valid input
+    /// is never copied.
+    pub fn apply(self, input: &[u8]) -> Cow<'_, [u8]> {
+        return match self {
+            InvalidUtf8::Report => Cow::Borrowed(input),
+            InvalidUtf8::Replace => match String::from_utf8_lossy(input) {
+                Cow::Borrowed(text) => Cow::Borrowed(text.as_bytes()),
+                Cow::Owned(text) => Cow::Owned(text.into_bytes()),
+            },
+        };
+    }
+}
+
+/// Parses a handling of invalid UTF-8: `report` or `replace`.
+pub fn parse_invalid_utf8(text: &str) -> Result<InvalidUtf8, String> {
+    return match text {
+        "report" => Ok(InvalidUtf8::Report),
+        "replace" => Ok(InvalidUtf8::Replace),
+        _ => Err(format!("expected report or replace, got {:?}", text)),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_bom() {
+        assert_eq!(strip_bom(b"\xEF\xBB\xBFone"), b"one");
+        assert_eq!(strip_bom(b"one"), b"one");
+        assert_eq!(strip_bom(b"one\xEF\xBB\xBF"), b"one\xEF\xBB\xBF");
+    }
+
+    #[test]
+    fn test_content_lines() {
+        let lines = content_lines(b"\xEF\xBB\xBFone\r\n\r\n \t\ntwo\r\n\n\n").collect::<Vec<_>>();
+        assert_eq!(lines, vec![(1, &b"one"[..]), (4, &b"two"[..])]);
+    }
+
+    #[test]
+    fn test_invalid_utf8() {
+        let latin1 = b"caf\xE9\n1 red";
+        assert_eq!(InvalidUtf8::Report.apply(latin1), &latin1[..]);
+        assert_eq!(InvalidUtf8::Replace.apply(latin1), "caf\u{FFFD}\n1 red".as_bytes());
+        assert!(matches!(InvalidUtf8::Replace.apply(b"valid"), Cow::Borrowed(_)));
+        assert_eq!(parse_invalid_utf8("replace"), Ok(InvalidUtf8::Replace));
+        assert!(parse_invalid_utf8("ignore").is_err());
+    }
+}
@@ -0,0 +1,323 @@
+//! Trebuchet calibration, day 1 of Advent of Code 2023.
+//!
+//! Each line of a calibration document hides a calibration number: the first and the last
+//! digit of the line, written either as a digit or spelt out ("one" to "nine"), combined
+//! into a two-digit number. The calibration value of a document is the sum of its numbers.
+//!
+//! ```no_run
+//! use aoc::normalize::InvalidUtf8;
+//! use day_1::{read_calibration_digits, Scanner};
+//!
+//! for digit in read_calibration_digits("./src/input.txt", Scanner::detect(), InvalidUtf8::Report)? {
+//!     match digit {
+//!         Ok(digit) => println!("{}{}", digit.first_digit, digit.second_digit),
+//!         Err(error) => eprintln!("{}", error),
+//!     }
+//! }
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+pub mod scan;
+
+pub use scan::{parse_matching, Matching, Scanner};
+
+use aoc::input_file::{InputFile, LineCursor, LineError};
+use aoc::normalize::{content_lines, InvalidUtf8};
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::path::Path;
+use std::str;
+
+/// The first and last digit found on a line.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct CalibrationDigit {
+    pub first_digit: u64,
+    pub second_digit: u64,
+}
+
+/// A calibration number, or the sum of several of them.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct CalibrationNumber {
+    pub value: u64,
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub enum ParseError {
+    /// The line contains neither digits nor spelt digits.
+    NoDigits,
+    /// The line is not valid UTF-8 and invalid sequences are reported.
+    InvalidUtf8,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self {
+            ParseError::NoDigits => write!(f, "line contains no digits"),
+            ParseError::InvalidUtf8 => write!(f, "line is not valid UTF-8"),
+        };
+    }
+}
+
+impl Error for ParseError {}
+
+/// Lazily reads the calibration digits of each line of a file.
+pub struct CalibrationDigits {
+    cursor: LineCursor,
+    scanner: Scanner,
+    invalid_utf8: InvalidUtf8,
+}
+
+impl Iterator for CalibrationDigits {
+    type Item = Result<CalibrationDigit, LineError<ParseError>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (line_number, line) = self.cursor.next_content_line()?;
+        return Some(
+            line_digit(line, self.scanner, self.invalid_utf8)
+                .map_err(|error| LineError { line: line_number, error })
+        );
+    }
+}
+
+/// Memory-maps the file at `path` and returns a reader over the digits of its lines.
+pub fn read_calibration_digits<P: AsRef<Path>>(path: P, scanner: Scanner, invalid_utf8: InvalidUtf8) -> io::Result<CalibrationDigits> {
+    return Ok(CalibrationDigits {
+        cursor: InputFile::map(path)?.into_cursor(),
+        scanner,
+        invalid_utf8,
+    });
+}
+
+/// Sums the calibration numbers of every line of `input`, skipping blank lines and a byte
+/// order mark.
+pub fn calibrate(input: &[u8], scanner: Scanner, invalid_utf8: InvalidUtf8) -> Result<CalibrationNumber, LineError<ParseError>> {
+    let mut total = CalibrationNumber { value: 0 };
+    for (line_number, line) in content_lines(input) {
+        let digit = line_digit(line, scanner, invalid_utf8).map_err(|error| LineError { line: line_number, error })?;
+        total.value += create_calibration_number(digit).value;
+    }
+    return Ok(total);
+}
+
+/// Calibration value of the file at `filename`, using the fastest available scanner.
+///
+/// # Panics
+///
+/// Panics if the file cannot be read or a line holds no digits.
+pub fn process_input(filename: String) -> CalibrationNumber {
+    return process_input_with(filename, Scanner::detect(), InvalidUtf8::Report);
+}
+
+/// Like [`process_input`], with an explicit scanner and handling of invalid UTF-8.
+pub fn process_input_with(filename: String, scanner: Scanner, invalid_utf8: InvalidUtf8) -> CalibrationNumber {
+    return read_calibration_digits(filename, scanner, invalid_utf8)
+        .unwrap()
+        .map(|digit| digit.unwrap())
+        .map(create_calibration_number)
+        .fold(
+            CalibrationNumber { value: 0 },
+            |acc, number| CalibrationNumber { value: acc.value + number.value },
+        );
+}
+
+/// The digits of a line, checking that it is UTF-8 unless invalid sequences are replaced.
+///
+/// Replacing needs no copy: no invalid sequence contains an ASCII byte, so the scanners
+/// find the same digits in the raw bytes as in the text with U+FFFD in their place.
+fn line_digit(line: &[u8], scanner: Scanner, invalid_utf8: InvalidUtf8) -> Result<CalibrationDigit, ParseError> {
+    if invalid_utf8 == InvalidUtf8::Report && str::from_utf8(line).is_err() {
+        return Err(ParseError::InvalidUtf8);
+    }
+    return scanner.calibration_digit(line).ok_or(ParseError::NoDigits);
+}
+
+/// Character based reference implementation the byte scanners are checked against.
+/// Spelt digits may overlap, so "oneight" yields 1 and 8, as under [`Matching::Overlapping`].
+pub fn parse_calibration_digit(line: &str) -> Result<CalibrationDigit, ParseError> {
+    let characters = line.split("").collect::<Vec<&str>>();
+
+    let mut digits: Vec<u64> = Vec::new();
+    let mut processed_characters = "".to_owned();
+    for character in characters {
+        if let Ok(digit) = character.parse::<u64>() {
+            digits.push(digit)
+        }
+        processed_characters.push_str(character);
+
+        if processed_characters.ends_with("one") {
+            digits.push(1)
+        } else if processed_characters.ends_with("two") {
+            digits.push(2)
+        } else if processed_characters.ends_with("three") {
+            digits.push(3)
+        } else if processed_characters.ends_with("four") {
+            digits.push(4)
+        } else if processed_characters.ends_with("five") {
+            digits.push(5)
+        } else if processed_characters.ends_with("six") {
+            digits.push(6)
+        } else if processed_characters.ends_with("seven") {
+            digits.push(7)
+        } else if processed_characters.ends_with("eight") {
+            digits.push(8)
+        } else if processed_characters.ends_with("nine") {
+            digits.push(9)
+        }
+    }
+
+    if digits.is_empty() {
+        return Err(ParseError::NoDigits);
+    }
+    return Ok(CalibrationDigit {
+        first_digit: digits[0],
+        second_digit: digits[digits.len() - 1],
+    });
+}
+
+/// Combines the two digits into a two-digit number, so 1 and 9 become 19.
+pub fn create_calibration_number(digit: CalibrationDigit) -> CalibrationNumber {
+    let concat_digits = digit.first_digit.to_string() + &digit.second_digit.to_string();
+    return CalibrationNumber {
+        value: concat_digits.parse::<u64>().unwrap()
+    };
+}
+
+/// Sums calibration numbers into a calibration value.
+pub fn sum_calibration_digits(numbers: Vec<CalibrationNumber>) -> CalibrationNumber {
+    return numbers
+        .iter()
+        .fold(
+            CalibrationNumber { value: 0 },
+            |acc, number| CalibrationNumber { value: acc.value + number.value },
+        );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parses_single_line_with_two_digits() {
+        let actual = parse_calibration_digit("1abc2");
+
+        assert_eq!(
+            actual,
+            Ok(CalibrationDigit {
+                first_digit: 1,
+                second_digit: 2,
+            })
+        )
+    }
+
+    #[test]
+    fn test_parse_single_line_with_one_digit() {
+        let actual = parse_calibration_digit("a7bc");
+
+        assert_eq!(
+            actual,
+            Ok(CalibrationDigit {
+                first_digit: 7,
+                second_digit: 7,
+            })
+        )
+    }
+
+    #[test]
+    fn test_parse_line_wth_multiple_digits() {
+        let actual = parse_calibration_digit("a1b2c3d4e5f");
+        assert_eq!(
+            actual,
+            Ok(CalibrationDigit {
+                first_digit: 1,
+                second_digit: 5,
+            })
+        )
+    }
+
+    #[test]
+    fn test_parse_line_with_spelt_one() {
+        let actual = parse_calibration_digit("one");
+        assert_eq!(
+            actual,
+            Ok(CalibrationDigit {
+                first_digit: 1,
+                second_digit: 1,
+            })
+        )
+    }
+
+    #[test]
+    fn test_create_calibration_number() {
+        let actual = create_calibration_number(CalibrationDigit {
+            first_digit: 1,
+            second_digit: 9,
+        });
+
+        assert_eq!(
+            actual,
+            CalibrationNumber { value: 19 }
+        )
+    }
+
+    #[test]
+    fn test_calibrate_reports_line_without_digits() {
+        let actual = calibrate(b"1abc2\nabc\n", Scanner::detect(), InvalidUtf8::Report);
+        assert_eq!(
+            actual,
+            Err(LineError { line: 2, error: ParseError::NoDigits })
+        )
+    }
+
+    #[test]
+    fn test_calibrate_normalizes_input() {
+        let plain = calibrate(b"1abc2\ntwo1nine\n", Scanner::detect(), InvalidUtf8::Report);
+        assert_eq!(calibrate(b"\xEF\xBB\xBF1abc2\r\n\r\ntwo1nine\r\n\n", Scanner::detect(), InvalidUtf8::Report), plain);
+        assert_eq!(plain, Ok(CalibrationNumber { value: 41 }));
+    }
+
+    #[test]
+    fn test_calibrate_invalid_utf8() {
+        let latin1 = b"1abc2\ncaf\xE9 two1nine\n";
+        assert_eq!(
+            calibrate(latin1, Scanner::detect(), InvalidUtf8::Report),
+            Err(LineError { line: 2, error: ParseError::InvalidUtf8 })
+        );
+        for scanner in Scanner::available() {
+            assert_eq!(calibrate(latin1, scanner, InvalidUtf8::Replace), Ok(CalibrationNumber { value: 41 }));
+        }
+    }
+
+    #[test]
+    fn test_read_calibration_digits() {
+        let actual = read_calibration_digits("./src/test-input.txt", Scanner::detect(), InvalidUtf8::Report)
+            .unwrap()
+            .collect::<Vec<_>>();
+        assert_eq!(actual.len(), 4);
+        assert_eq!(
+            actual[0],
+            Ok(CalibrationDigit {
+                first_digit: 1,
+                second_digit: 2,
+            })
+        )
+    }
+
+    #[test]
+    fn test_process_test_input() {
+        let actual = process_input("./src/test-input.txt".to_string());
+        assert_eq!(
+            actual,
+            CalibrationNumber { value: 142 }
+        )
+    }
+
+    #[test]
+    fn test_process_test_input_part_two() {
+        let actual = process_input("./src/test-input-part-2.txt".to_string());
+        assert_eq!(
+            actual,
+            CalibrationNumber { value: 299 }
+        )
+    }
+}
\ No newline at end of file
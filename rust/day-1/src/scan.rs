@@ -0,0 +1,370 @@
+//! Byte-level calibration digit scanning with SIMD acceleration.
+//!
+//! The SIMD paths build a bitmask of candidate bytes (ASCII digits and the first letters of
+//! the spelt digits: o, t, f, s, e, n) for 16 or 32 bytes at a time, so only candidates have
+//! to be checked against the digit words. On x86_64 SSE2 is always available and AVX2 is
+//! detected at runtime; every other target uses the scalar scanner.
+//!
+//! Spelt digits that share letters are read according to a [`Matching`]. The SIMD paths
+//! implement the default, overlapping matching; the other modes are always scanned scalar.
+
+use crate::CalibrationDigit;
+use std::cmp::Reverse;
+
+#[cfg(target_arch = "x86_64")]
+use std::arch::x86_64::*;
+
+const WORDS: [&[u8]; 9] = [
+    b"one", b"two", b"three", b"four", b"five", b"six", b"seven", b"eight", b"nine",
+];
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+#[cfg_attr(not(target_arch = "x86_64"), allow(dead_code))]
+enum Kind {
+    Scalar,
+    Sse2,
+    Avx2,
+}
+
+/// How spelt digits that share letters are read.
+#[derive(PartialEq, Debug, Clone, Copy, Default)]
+pub enum Matching {
+    /// Every word counts, so "oneight" holds 1 and 8.
+    #[default]
+    Overlapping,
+    /// Words are consumed from left to right, so the "e" of "oneight" belongs to "one" and
+    /// only 1 is read.
+    Greedy,
+    /// A word beats the shorter words it overlaps wherever they start, so "oneight" is read
+    /// as 8. Of two overlapping words of the same length the leftmost wins.
+    Longest,
+}
+
+/// Parses a matching mode: `overlapping`, `greedy` or `longest`.
+pub fn parse_matching(text: &str) -> Result<Matching, String> {
+    return match text {
+        "overlapping" => Ok(Matching::Overlapping),
+        "greedy" => Ok(Matching::Greedy),
+        "longest" => Ok(Matching::Longest),
+        _ => Err(format!("expected overlapping, greedy or longest, got {:?}", text)),
+    };
+}
+
+/// A digit scanner. SIMD scanners can only be obtained when the CPU supports them.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct Scanner {
+    kind: Kind,
+    matching: Matching,
+}
+
+impl Scanner {
+    pub fn scalar() -> Scanner {
+        return Scanner { kind: Kind::Scalar, matching: Matching::Overlapping };
+    }
+
+    /// This scanner reading spelt digits according to `matching`.
+    pub fn with_matching(self, matching: Matching) -> Scanner {
+        return Scanner { matching, ..self };
+    }
+
+    /// The fastest scanner supported by the running CPU.
+    pub fn detect() -> Scanner {
+        return *Scanner::available().last().unwrap();
+    }
+
+    /// All scanners supported by the running CPU, from slowest to fastest.
+    pub fn available() -> Vec<Scanner> {
+        #[allow(unused_mut)]
+        let mut scanners = vec![Scanner::scalar()];
+        #[cfg(target_arch = "x86_64")]
+        {
+            scanners.push(Scanner { kind: Kind::Sse2, matching: Matching::Overlapping });
+            if is_x86_feature_detected!("avx2") {
+                scanners.push(Scanner { kind: Kind::Avx2, matching: Matching::Overlapping });
+            }
+        }
+        return scanners;
+    }
+
+    pub fn name(&self) -> &'static str {
+        return match self.kind {
+            Kind::Scalar => "scalar",
+            Kind::Sse2 => "sse2",
+            Kind::Avx2 => "avx2",
+        };
+    }
+
+    /// Finds the first and last digit of `line`, spelt or not.
+    /// By default spelt digits may overlap, so "oneight" yields 1 and 8.
+    pub fn calibration_digit(&self, line: &[u8]) -> Option<CalibrationDigit> {
+        let (first_digit, second_digit) = match (self.matching, self.kind) {
+            (Matching::Greedy, _) => digits_greedy(line)?,
+            (Matching::Longest, _) => digits_longest(line)?,
+            (Matching::Overlapping, Kind::Scalar) => (first_scalar(line)?, last_scalar(line)?),
+            // SAFETY: SIMD kinds are only handed out by `available` when the CPU supports them.
+            #[cfg(target_arch = "x86_64")]
+            (Matching::Overlapping, Kind::Sse2) => unsafe { digits_sse2(line)? },
+            #[cfg(target_arch = "x86_64")]
+            (Matching::Overlapping, Kind::Avx2) => unsafe { digits_avx2(line)? },
+            #[cfg(not(target_arch = "x86_64"))]
+            _ => unreachable!("SIMD scanners are only available on x86_64"),
+        };
+        return Some(CalibrationDigit {
+            first_digit,
+            second_digit,
+        });
+    }
+}
+
+fn digit_at(line: &[u8], index: usize) -> Option<u64> {
+    return word_at(line, index).map(|(digit, _)| digit);
+}
+
+/// The digit starting at `index` together with its length in bytes.
+fn word_at(line: &[u8], index: usize) -> Option<(u64, usize)> {
+    let rest = &line[index..];
+    let candidates: &[u64] = match rest[0] {
+        byte @ b'0'..=b'9' => return Some(((byte - b'0') as u64, 1)),
+        b'o' => &[1],
+        b't' => &[2, 3],
+        b'f' => &[4, 5],
+        b's' => &[6, 7],
+        b'e' => &[8],
+        b'n' => &[9],
+        _ => return None,
+    };
+    return candidates
+        .iter()
+        .map(|&digit| (digit, WORDS[digit as usize - 1].len()))
+        .find(|&(digit, _)| rest.starts_with(WORDS[digit as usize - 1]));
+}
+
+/// Every digit of `line`, spelt or not, as `(start, length, digit)` in line order.
+fn words(line: &[u8]) -> Vec<(usize, usize, u64)> {
+    return (0..line.len())
+        .filter_map(|index| word_at(line, index).map(|(digit, length)| (index, length, digit)))
+        .collect();
+}
+
+/// First and last digit when words are consumed from left to right.
+fn digits_greedy(line: &[u8]) -> Option<(u64, u64)> {
+    let mut end = 0;
+    let mut kept = Vec::new();
+    for (start, length, digit) in words(line) {
+        if start >= end {
+            kept.push(digit);
+            end = start + length;
+        }
+    }
+    return Some((*kept.first()?, *kept.last()?));
+}
+
+/// First and last digit when longer words win over the words they overlap.
+fn digits_longest(line: &[u8]) -> Option<(u64, u64)> {
+    let mut by_length = words(line);
+    by_length.sort_by_key(|&(start, length, _)| (Reverse(length), start));
+    let mut kept: Vec<(usize, usize, u64)> = Vec::new();
+    for (start, length, digit) in by_length {
+        if kept.iter().all(|&(other, other_length, _)| start >= other + other_length || other >= start + length) {
+            kept.push((start, length, digit));
+        }
+    }
+    return Some((kept.iter().min()?.2, kept.iter().max()?.2));
+}
+
+fn first_scalar(line: &[u8]) -> Option<u64> {
+    return (0..line.len()).find_map(|index| digit_at(line, index));
+}
+
+fn last_scalar(line: &[u8]) -> Option<u64> {
+    return (0..line.len()).rev().find_map(|index| digit_at(line, index));
+}
+
+// Short tails are copied into a zero padded block, zero bytes are never candidates.
+#[cfg(target_arch = "x86_64")]
+#[inline(always)]
+fn block_mask<const WIDTH: usize>(block: &[u8], mask: &impl Fn(*const u8) -> u32) -> u32 {
+    if block.len() >= WIDTH {
+        return mask(block.as_ptr());
+    }
+    let mut padded = [0u8; 32];
+    padded[..block.len()].copy_from_slice(block);
+    return mask(padded.as_ptr());
+}
+
+// Scans `line` in blocks of `WIDTH` bytes using `mask` to find the candidates of each block.
+// Inlined into the `target_feature` functions below so the intrinsics are inlined as well.
+#[cfg(target_arch = "x86_64")]
+#[inline(always)]
+fn first_blocked<const WIDTH: usize>(line: &[u8], mask: impl Fn(*const u8) -> u32) -> Option<u64> {
+    let mut start = 0;
+    while start < line.len() {
+        let block = &line[start..];
+        let mut candidates = block_mask::<WIDTH>(block, &mask);
+        while candidates != 0 {
+            if let Some(digit) = digit_at(line, start + candidates.trailing_zeros() as usize) {
+                return Some(digit);
+            }
+            candidates &= candidates - 1;
+        }
+        start += WIDTH;
+    }
+    return None;
+}
+
+#[cfg(target_arch = "x86_64")]
+#[inline(always)]
+fn last_blocked<const WIDTH: usize>(line: &[u8], mask: impl Fn(*const u8) -> u32) -> Option<u64> {
+    if line.is_empty() {
+        return None;
+    }
+    let mut start = (line.len() - 1) / WIDTH * WIDTH;
+    loop {
+        let block = &line[start..];
+        let mut candidates = block_mask::<WIDTH>(block, &mask);
+        while candidates != 0 {
+            let bit = 31 - candidates.leading_zeros();
+            if let Some(digit) = digit_at(line, start + bit as usize) {
+                return Some(digit);
+            }
+            candidates &= !(1 << bit);
+        }
+        if start == 0 {
+            return None;
+        }
+        start -= WIDTH;
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse2")]
+unsafe fn digits_sse2(line: &[u8]) -> Option<(u64, u64)> {
+    // SAFETY: the blocked scans only pass pointers to blocks of at least 16 bytes.
+    let mask = |block| unsafe { sse2_mask(block) };
+    return Some((first_blocked::<16>(line, mask)?, last_blocked::<16>(line, mask)?));
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn digits_avx2(line: &[u8]) -> Option<(u64, u64)> {
+    // SAFETY: the blocked scans only pass pointers to blocks of at least 32 bytes.
+    let mask = |block| unsafe { avx2_mask(block) };
+    return Some((first_blocked::<32>(line, mask)?, last_blocked::<32>(line, mask)?));
+}
+
+#[cfg(target_arch = "x86_64")]
+#[inline]
+#[target_feature(enable = "sse2")]
+unsafe fn sse2_mask(block: *const u8) -> u32 {
+    let bytes = _mm_loadu_si128(block as *const __m128i);
+    // Bytes above 0x7f compare as negative, so they never count as digits.
+    let digits = _mm_and_si128(
+        _mm_cmpgt_epi8(bytes, _mm_set1_epi8(b'0' as i8 - 1)),
+        _mm_cmplt_epi8(bytes, _mm_set1_epi8(b'9' as i8 + 1)),
+    );
+    let mut candidates = digits;
+    for letter in [b'o', b't', b'f', b's', b'e', b'n'] {
+        candidates = _mm_or_si128(candidates, _mm_cmpeq_epi8(bytes, _mm_set1_epi8(letter as i8)));
+    }
+    return _mm_movemask_epi8(candidates) as u32;
+}
+
+#[cfg(target_arch = "x86_64")]
+#[inline]
+#[target_feature(enable = "avx2")]
+unsafe fn avx2_mask(block: *const u8) -> u32 {
+    let bytes = _mm256_loadu_si256(block as *const __m256i);
+    let digits = _mm256_and_si256(
+        _mm256_cmpgt_epi8(bytes, _mm256_set1_epi8(b'0' as i8 - 1)),
+        _mm256_cmpgt_epi8(_mm256_set1_epi8(b'9' as i8 + 1), bytes),
+    );
+    let mut candidates = digits;
+    for letter in [b'o', b't', b'f', b's', b'e', b'n'] {
+        candidates = _mm256_or_si256(candidates, _mm256_cmpeq_epi8(bytes, _mm256_set1_epi8(letter as i8)));
+    }
+    return _mm256_movemask_epi8(candidates) as u32;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_calibration_digit;
+
+    fn assert_matches_parser(line: &str) {
+        for scanner in Scanner::available() {
+            assert_eq!(
+                scanner.calibration_digit(line.as_bytes()),
+                parse_calibration_digit(line).ok(),
+                "{} scanner on {:?}",
+                scanner.name(),
+                line
+            )
+        }
+    }
+
+    #[test]
+    fn test_scanners_match_parser_on_test_inputs() {
+        let inputs = include_str!("test-input.txt").to_string() + "\n" + include_str!("test-input-part-2.txt");
+        for line in inputs.lines() {
+            assert_matches_parser(line);
+        }
+    }
+
+    #[test]
+    fn test_scanners_across_block_boundaries() {
+        assert_matches_parser("xxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxtwo");
+        assert_matches_parser("sevxxxxxxxxxxxxxxxxxxxxxxxxxxxxxseven3xxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxfi");
+        assert_matches_parser("abcdefghijklmnoneightxyzxyzxyzxyzxyzxyzxyzxyzxyzeightwo");
+        assert_matches_parser("4xxxxxxxxxxxxxxxxxxxxxxxxxxxxxxx");
+    }
+
+    #[test]
+    fn test_scanners_ignore_non_ascii_bytes() {
+        let line = [&[0xffu8; 20][..], b"1", &[0xb0u8; 20][..], b"nine", &[0x80u8; 20][..]].concat();
+        for scanner in Scanner::available() {
+            assert_eq!(
+                scanner.calibration_digit(&line),
+                Some(CalibrationDigit {
+                    first_digit: 1,
+                    second_digit: 9,
+                })
+            )
+        }
+    }
+
+    #[test]
+    fn test_scanners_without_digits() {
+        for scanner in Scanner::available() {
+            assert_eq!(scanner.calibration_digit(b"abcdefghijklmnopqrstuvwxyzabcdefghijkl"), None);
+            assert_eq!(scanner.calibration_digit(b""), None);
+        }
+    }
+
+    #[test]
+    fn test_matching_modes() {
+        let cases = [
+            ("twone", [(2, 1), (2, 2), (2, 2)]),
+            ("eightwo", [(8, 2), (8, 8), (8, 8)]),
+            ("sevenine", [(7, 9), (7, 7), (7, 7)]),
+            ("oneight", [(1, 8), (1, 1), (8, 8)]),
+            ("oneightwo", [(1, 2), (1, 2), (8, 8)]),
+        ];
+        for (line, expected) in cases {
+            for (matching, (first_digit, second_digit)) in [Matching::Overlapping, Matching::Greedy, Matching::Longest].into_iter().zip(expected) {
+                for scanner in Scanner::available() {
+                    assert_eq!(
+                        scanner.with_matching(matching).calibration_digit(line.as_bytes()),
+                        Some(CalibrationDigit { first_digit, second_digit }),
+                        "{:?} {} scanner on {:?}",
+                        matching,
+                        scanner.name(),
+                        line
+                    );
+                }
+            }
+        }
+        assert_eq!(Scanner::scalar().with_matching(Matching::Greedy).calibration_digit(b"xyz"), None);
+        assert_eq!(parse_matching("longest"), Ok(Matching::Longest));
+        assert!(parse_matching("shortest").is_err());
+    }
+}
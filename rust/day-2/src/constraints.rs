@@ -0,0 +1,281 @@
+//! Linear constraints on the cubes of a set, a generalisation of the per-colour bag limits.
+//!
+//! Constraints are loaded from a TOML rules file, each with a name and a linear
+//! (in)equality over the counts of `red`, `green` and `blue` in a set:
+//!
+//! ```toml
+//! [[constraint]]
+//! name = "at most 30 cubes"
+//! rule = "red + green + blue <= 30"
+//!
+//! [[constraint]]
+//! name = "green in every set"
+//! rule = "green >= 1"
+//!
+//! [[constraint]]
+//! name = "more blue than red"
+//! rule = "2 * blue - red > 0"
+//! ```
+//!
+//! A set violates a constraint when its counts, with 0 for missing colours, do not satisfy
+//! the rule.
+
+use crate::Color::{BLUE, GREEN, RED};
+use crate::{Color, Game, Set};
+use serde::Deserialize;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum Comparison {
+    Less,
+    LessOrEqual,
+    Equal,
+    GreaterOrEqual,
+    Greater,
+}
+
+/// `red_coefficient * red + green_coefficient * green + blue_coefficient * blue <comparison> bound`.
+#[derive(PartialEq, Debug, Clone)]
+pub struct Constraint {
+    pub name: String,
+    /// Coefficients of red, green and blue.
+    pub coefficients: [i64; 3],
+    pub comparison: Comparison,
+    pub bound: i64,
+}
+
+/// A violated constraint of one set of a game, numbered from 1.
+#[derive(PartialEq, Debug, Clone)]
+pub struct Violation<'a> {
+    pub set: usize,
+    pub constraint: &'a str,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RulesFile {
+    #[serde(default)]
+    constraint: Vec<RuleEntry>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RuleEntry {
+    name: String,
+    rule: String,
+}
+
+impl Constraint {
+    /// Parses a rule such as `red + 2 * blue <= 20` into a named constraint.
+    pub fn parse(name: &str, rule: &str) -> Result<Constraint, String> {
+        let operators = [("<=", Comparison::LessOrEqual), (">=", Comparison::GreaterOrEqual), ("==", Comparison::Equal), ("<", Comparison::Less), (">", Comparison::Greater)];
+        let (index, operator, comparison) = operators.iter()
+            .filter_map(|(operator, comparison)| rule.find(operator).map(|index| (index, *operator, *comparison)))
+            .min_by_key(|(index, operator, _)| (*index, usize::MAX - operator.len()))
+            .ok_or(format!("{:?} has no comparison", rule))?;
+        let (left, right) = (&rule[..index], &rule[index + operator.len()..]);
+        let (left_coefficients, left_constant) = parse_side(left)?;
+        let (right_coefficients, right_constant) = parse_side(right)?;
+        let too_large = || format!("{:?} has a coefficient or constant too large", rule);
+        let mut coefficients = [0; 3];
+        for color in 0..3 {
+            coefficients[color] = left_coefficients[color].checked_sub(right_coefficients[color]).ok_or_else(too_large)?;
+        }
+        return Ok(Constraint {
+            name: name.to_string(),
+            coefficients,
+            comparison,
+            bound: right_constant.checked_sub(left_constant).ok_or_else(too_large)?,
+        });
+    }
+
+    /// Whether the cubes of `set` meet the constraint.
+    pub fn is_satisfied_by(&self, set: &Set) -> bool {
+        // The weighted sum less the bound, as the difference of its positive and negative
+        // parts. Each part is kept as carries and a remainder, so it is exact for any set.
+        let mut positive = (0, 0);
+        let mut negative = (0, 0);
+        for cube in &set.cubes {
+            let coefficient = self.coefficients[color_index(&cube.color)];
+            let term = coefficient.unsigned_abs() as u128 * cube.count as u128;
+            add_wide(if coefficient < 0 { &mut negative } else { &mut positive }, term);
+        }
+        add_wide(if self.bound < 0 { &mut positive } else { &mut negative }, self.bound.unsigned_abs() as u128);
+        let ordering = positive.cmp(&negative);
+        return match self.comparison {
+            Comparison::Less => ordering.is_lt(),
+            Comparison::LessOrEqual => ordering.is_le(),
+            Comparison::Equal => ordering.is_eq(),
+            Comparison::GreaterOrEqual => ordering.is_ge(),
+            Comparison::Greater => ordering.is_gt(),
+        };
+    }
+}
+
+fn add_wide(sum: &mut (u128, u128), amount: u128) {
+    let (low, carried) = sum.1.overflowing_add(amount);
+    *sum = (sum.0 + carried as u128, low);
+}
+
+// Parses one side of a rule into coefficients per colour and a constant.
+fn parse_side(side: &str) -> Result<([i64; 3], i64), String> {
+    let spaced = side.replace('+', " + ").replace('-', " - ").replace('*', " * ");
+    let mut tokens = spaced.split_whitespace().peekable();
+    let unexpected = |token: &str| format!("unexpected {:?} in {:?}", token, side.trim());
+    let incomplete = || format!("incomplete expression {:?}", side.trim());
+    let too_large = || format!("{:?} has a coefficient or constant too large", side.trim());
+    let color = |name: &str| ["red", "green", "blue"].iter().position(|color| *color == name);
+
+    let mut coefficients = [0; 3];
+    let mut constant = 0;
+    let mut sign = 1;
+    loop {
+        while let Some(&operator @ ("+" | "-")) = tokens.peek() {
+            if operator == "-" {
+                sign = -sign;
+            }
+            tokens.next();
+        }
+        let token = tokens.next().ok_or_else(incomplete)?;
+        let (term, number): (&mut i64, i64) = if let Some(color) = color(token) {
+            (&mut coefficients[color], 1)
+        } else {
+            let number = token.parse::<i64>().map_err(|_| unexpected(token))?;
+            if tokens.peek() == Some(&"*") {
+                tokens.next();
+                let name = tokens.next().ok_or_else(incomplete)?;
+                (&mut coefficients[color(name).ok_or_else(|| unexpected(name))?], number)
+            } else {
+                (&mut constant, number)
+            }
+        };
+        *term = number.checked_mul(sign).and_then(|number| term.checked_add(number)).ok_or_else(too_large)?;
+        sign = match tokens.next() {
+            None => return Ok((coefficients, constant)),
+            Some("+") => 1,
+            Some("-") => -1,
+            Some(token) => return Err(unexpected(token)),
+        };
+    }
+}
+
+fn color_index(color: &Color) -> usize {
+    return match color {
+        RED => 0,
+        GREEN => 1,
+        BLUE => 2,
+    };
+}
+
+/// Loads the constraints of a rules file.
+pub fn load_constraints<P: AsRef<Path>>(path: P) -> io::Result<Vec<Constraint>> {
+    let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+    let file = toml::from_str::<RulesFile>(&fs::read_to_string(path)?).map_err(|error| invalid(error.to_string()))?;
+    return file.constraint.iter()
+        .map(|entry| Constraint::parse(&entry.name, &entry.rule).map_err(|error| invalid(format!("constraint {:?}: {}", entry.name, error))))
+        .collect();
+}
+
+/// Every constraint violated by every set of the game.
+pub fn violations<'a>(game: &Game, constraints: &'a [Constraint]) -> Vec<Violation<'a>> {
+    return game.sets.iter().enumerate()
+        .flat_map(|(index, set)| constraints.iter()
+            .filter(|constraint| !constraint.is_satisfied_by(set))
+            .map(move |constraint| Violation { set: index + 1, constraint: &constraint.name }))
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{is_game_possible, parse_game, parse_games, Cube};
+
+    #[test]
+    fn test_parse_constraint() {
+        assert_eq!(
+            Constraint::parse("mix", "red + 2 * blue - 3 <= green - 1").unwrap(),
+            Constraint { name: "mix".to_string(), coefficients: [1, -1, 2], comparison: Comparison::LessOrEqual, bound: 2 }
+        );
+        assert_eq!(Constraint::parse("g", "green>=1").unwrap().comparison, Comparison::GreaterOrEqual);
+        assert_eq!(Constraint::parse("n", "-red > -5").unwrap().coefficients, [-1, 0, 0]);
+        assert_eq!(Constraint::parse("t", "30 >= red + green + blue").unwrap(), Constraint {
+            name: "t".to_string(),
+            coefficients: [-1, -1, -1],
+            comparison: Comparison::GreaterOrEqual,
+            bound: -30,
+        });
+        assert!(Constraint::parse("x", "red + purple <= 3").is_err());
+        assert!(Constraint::parse("x", "red + <= 3").is_err());
+        assert!(Constraint::parse("x", "red 3 <= 3").is_err());
+        assert!(Constraint::parse("x", "red").is_err());
+        assert!(Constraint::parse("x", "9223372036854775807 * red + 9223372036854775807 * red <= 1").is_err());
+        assert!(Constraint::parse("x", "-9223372036854775807 - 2 <= red").is_err());
+        assert!(Constraint::parse("x", "9223372036854775807 * red <= -red").is_err());
+        assert!(Constraint::parse("x", "-1 >= 9223372036854775807").is_err());
+        assert_eq!(Constraint::parse("x", "-9223372036854775807 * red - red <= 0").unwrap().coefficients, [i64::MIN, 0, 0]);
+    }
+
+    #[test]
+    fn test_violations() {
+        let constraints = vec![
+            Constraint::parse("at most 30 cubes", "red + green + blue <= 30").unwrap(),
+            Constraint::parse("red and blue", "red + blue <= 20").unwrap(),
+            Constraint::parse("green in every set", "green >= 1").unwrap(),
+        ];
+        let game = parse_game("Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red").unwrap();
+        assert_eq!(
+            violations(&game, &constraints),
+            vec![
+                Violation { set: 1, constraint: "at most 30 cubes" },
+                Violation { set: 1, constraint: "red and blue" },
+            ]
+        );
+        let game = parse_game("Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green").unwrap();
+        assert_eq!(violations(&game, &constraints), vec![Violation { set: 1, constraint: "green in every set" }]);
+    }
+
+    #[test]
+    fn test_large_counts() {
+        let reds = |count: u64, cubes: usize| Set { cubes: vec![Cube { count, color: Color::RED }; cubes] };
+        assert!(!Constraint::parse("few", "red <= 5").unwrap().is_satisfied_by(&reds(u64::MAX, 1)));
+        assert!(Constraint::parse("any", "red >= 0").unwrap().is_satisfied_by(&reds(u64::MAX, 1)));
+
+        // Four terms of about 2^127 each pass the largest 128 bit sum.
+        let rising = Constraint { name: "rising".to_string(), coefficients: [i64::MAX, 0, 0], comparison: Comparison::GreaterOrEqual, bound: 0 };
+        assert!(rising.is_satisfied_by(&reds(u64::MAX, 4)));
+        assert!(!Constraint { comparison: Comparison::Less, bound: i64::MAX, ..rising.clone() }.is_satisfied_by(&reds(u64::MAX, 4)));
+        let falling = Constraint { coefficients: [i64::MIN, 0, i64::MAX], comparison: Comparison::LessOrEqual, ..rising };
+        assert!(falling.is_satisfied_by(&reds(u64::MAX, 4)));
+        let mut mixed = reds(u64::MAX, 4);
+        mixed.cubes.extend(vec![Cube { count: u64::MAX, color: Color::BLUE }; 4]);
+        // 4 * (2^64 - 1) * (2^63 - 1) - 4 * (2^64 - 1) * 2^63 is -4 * (2^64 - 1).
+        assert!(!Constraint { comparison: Comparison::Greater, bound: i64::MIN, ..falling.clone() }.is_satisfied_by(&mixed));
+        assert!(Constraint { comparison: Comparison::Less, bound: i64::MIN, ..falling }.is_satisfied_by(&mixed));
+    }
+
+    #[test]
+    fn test_bag_limits_as_constraints() {
+        let constraints = ["red <= 12", "green <= 13", "blue <= 14"]
+            .map(|rule| Constraint::parse(rule, rule).unwrap())
+            .to_vec();
+        for game in parse_games(include_bytes!("input.txt")).unwrap() {
+            assert_eq!(violations(&game, &constraints).is_empty(), is_game_possible(&game), "{}", game);
+        }
+    }
+
+    #[test]
+    fn test_load_constraints() {
+        let path = std::env::temp_dir().join(format!("day-2-constraints-{}.toml", std::process::id()));
+        fs::write(&path, "[[constraint]]\nname = \"total\"\nrule = \"red + green + blue <= 30\"\n\n[[constraint]]\nname = \"green\"\nrule = \"green >= 1\"\n").unwrap();
+        let constraints = load_constraints(&path).unwrap();
+        assert_eq!(constraints.iter().map(|constraint| constraint.name.as_str()).collect::<Vec<&str>>(), vec!["total", "green"]);
+
+        fs::write(&path, "[[constraint]]\nname = \"broken\"\nrule = \"red <\"\n").unwrap();
+        let error = load_constraints(&path).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert!(error.to_string().starts_with("constraint \"broken\""));
+        fs::remove_file(path).unwrap();
+    }
+}
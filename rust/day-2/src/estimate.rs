@@ -0,0 +1,261 @@
+//! Estimates what the bag held from the sets that were drawn out of it.
+//!
+//! Every set is modelled as a handful drawn without replacement from a bag of `r` red, `g`
+//! green and `b` blue cubes, and put back before the next one, so a set of `x` red, `y` green
+//! and `z` blue cubes has the multivariate hypergeometric likelihood
+//! `C(r, x) C(g, y) C(b, z) / C(r + g + b, x + y + z)`. Each colour gets its own prior over
+//! `0..=max` cubes, and the posterior is evaluated over the whole grid of bags.
+
+use crate::{Bag, Color, Game};
+
+/// A prior over the number of cubes of one colour.
+#[derive(PartialEq, Debug, Clone)]
+pub enum Prior {
+    /// Every count up to the maximum is equally likely.
+    Uniform,
+    /// Counts follow a Poisson distribution with the given mean.
+    Poisson(f64),
+}
+
+/// The largest `max` of [`Priors`]. The posterior grid has `(max + 1)^3` cells and takes
+/// O(max³) time per set, so 200 already means 64 MiB of weights.
+pub const MAX_COUNT: u64 = 200;
+
+/// The priors of all colours and the largest count of a colour that is considered.
+#[derive(PartialEq, Debug, Clone)]
+pub struct Priors {
+    pub red: Prior,
+    pub green: Prior,
+    pub blue: Prior,
+    pub max: u64,
+}
+
+impl Default for Priors {
+    fn default() -> Priors {
+        return Priors {
+            red: Prior::Uniform,
+            green: Prior::Uniform,
+            blue: Prior::Uniform,
+            max: 30,
+        };
+    }
+}
+
+/// The marginal posterior of one colour.
+#[derive(PartialEq, Debug, Clone)]
+pub struct ColorPosterior {
+    pub color: Color,
+    pub mean: f64,
+    /// The most likely count.
+    pub mode: u64,
+    /// Bounds of the central 90% credible interval.
+    pub lower: u64,
+    pub upper: u64,
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct Posterior {
+    /// The single most likely bag.
+    pub map: Bag,
+    pub red: ColorPosterior,
+    pub green: ColorPosterior,
+    pub blue: ColorPosterior,
+}
+
+/// Parses a prior such as `uniform` or `poisson:12.5`.
+pub fn parse_prior(text: &str) -> Result<Prior, String> {
+    if text == "uniform" {
+        return Ok(Prior::Uniform);
+    }
+    let mean = text
+        .strip_prefix("poisson:")
+        .ok_or(format!("unknown prior {:?}, expected uniform or poisson:<mean>", text))?;
+    return match mean.parse::<f64>() {
+        Ok(mean) if mean > 0.0 => Ok(Prior::Poisson(mean)),
+        _ => Err(format!("invalid poisson mean {:?}", mean)),
+    };
+}
+
+/// The posterior over the bags that could have produced every set of `games`.
+/// `None` if a game shows more cubes of a colour than `priors.max`, or if `priors.max` is
+/// above [`MAX_COUNT`].
+pub fn estimate_bag(games: &[Game], priors: &Priors) -> Option<Posterior> {
+    if priors.max > MAX_COUNT {
+        return None;
+    }
+    let observed = games.iter().flat_map(|game| &game.sets).map(|set| {
+        let mut counts = [0u64; 3];
+        for cube in &set.cubes {
+            let count = &mut counts[color_index(&cube.color)];
+            *count = count.saturating_add(cube.count);
+        }
+        counts
+    }).collect::<Vec<[u64; 3]>>();
+    // Repeated colours of a set are drawn together, so the bag needs their total.
+    let minimum = observed.iter().fold([0u64; 3], |acc, counts| {
+        [acc[0].max(counts[0]), acc[1].max(counts[1]), acc[2].max(counts[2])]
+    });
+    if minimum.iter().any(|&count| count > priors.max) {
+        return None;
+    }
+
+    let ln_factorials = ln_factorials(3 * priors.max as usize);
+    let ln_choose = |n: u64, k: u64| {
+        if k > n {
+            return f64::NEG_INFINITY;
+        }
+        return ln_factorials[n as usize] - ln_factorials[k as usize] - ln_factorials[(n - k) as usize];
+    };
+    let ln_priors = [&priors.red, &priors.green, &priors.blue].map(|prior| {
+        (0..=priors.max).map(|count| ln_prior(prior, count, &ln_factorials)).collect::<Vec<f64>>()
+    });
+
+    // Bags below the observed maxima cannot have produced the sets and keep a weight of zero.
+    let size = priors.max as usize + 1;
+    let cells = size.checked_mul(size)?.checked_mul(size)?;
+    let mut ln_weights = vec![f64::NEG_INFINITY; cells];
+    for red in minimum[0]..=priors.max {
+        for green in minimum[1]..=priors.max {
+            for blue in minimum[2]..=priors.max {
+                let mut ln_weight = ln_priors[0][red as usize] + ln_priors[1][green as usize] + ln_priors[2][blue as usize];
+                for counts in &observed {
+                    ln_weight += ln_choose(red, counts[0]) + ln_choose(green, counts[1]) + ln_choose(blue, counts[2])
+                        - ln_choose(red + green + blue, counts.iter().sum());
+                }
+                ln_weights[(red as usize * size + green as usize) * size + blue as usize] = ln_weight;
+            }
+        }
+    }
+
+    let (map_cell, ln_max) = ln_weights.iter().copied().enumerate()
+        .fold((0, f64::NEG_INFINITY), |best, (cell, ln_weight)| if ln_weight > best.1 { (cell, ln_weight) } else { best });
+    if ln_max == f64::NEG_INFINITY {
+        return None;
+    }
+    let mut marginals = [vec![0.0; size], vec![0.0; size], vec![0.0; size]];
+    let mut total = 0.0;
+    for (cell, ln_weight) in ln_weights.iter().enumerate() {
+        let weight = (ln_weight - ln_max).exp();
+        total += weight;
+        marginals[0][cell / (size * size)] += weight;
+        marginals[1][cell / size % size] += weight;
+        marginals[2][cell % size] += weight;
+    }
+
+    let [red, green, blue] = marginals;
+    return Some(Posterior {
+        map: Bag {
+            red: (map_cell / (size * size)) as u64,
+            green: (map_cell / size % size) as u64,
+            blue: (map_cell % size) as u64,
+        },
+        red: summarize_marginal(Color::RED, &red, total),
+        green: summarize_marginal(Color::GREEN, &green, total),
+        blue: summarize_marginal(Color::BLUE, &blue, total),
+    });
+}
+
+fn summarize_marginal(color: Color, weights: &[f64], total: f64) -> ColorPosterior {
+    let probabilities = weights.iter().map(|weight| weight / total).collect::<Vec<f64>>();
+    let quantile = |level: f64| {
+        let mut cumulative = 0.0;
+        for (count, probability) in probabilities.iter().enumerate() {
+            cumulative += probability;
+            if cumulative >= level - 1e-12 {
+                return count as u64;
+            }
+        }
+        return (probabilities.len() - 1) as u64;
+    };
+    return ColorPosterior {
+        color,
+        mean: probabilities.iter().enumerate().map(|(count, probability)| count as f64 * probability).sum(),
+        mode: probabilities.iter().enumerate()
+            .fold((0, 0.0), |best, (count, &probability)| if probability > best.1 { (count, probability) } else { best })
+            .0 as u64,
+        lower: quantile(0.05),
+        upper: quantile(0.95),
+    };
+}
+
+fn ln_prior(prior: &Prior, count: u64, ln_factorials: &[f64]) -> f64 {
+    return match prior {
+        Prior::Uniform => 0.0,
+        Prior::Poisson(mean) => count as f64 * mean.ln() - mean - ln_factorials[count as usize],
+    };
+}
+
+fn ln_factorials(up_to: usize) -> Vec<f64> {
+    let mut ln_factorials = vec![0.0; up_to + 1];
+    for n in 1..=up_to {
+        ln_factorials[n] = ln_factorials[n - 1] + (n as f64).ln();
+    }
+    return ln_factorials;
+}
+
+fn color_index(color: &Color) -> usize {
+    return match color {
+        Color::RED => 0,
+        Color::GREEN => 1,
+        Color::BLUE => 2,
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_games;
+
+    #[test]
+    fn test_estimate_single_colour() {
+        let games = parse_games(b"Game 1: 5 red; 5 red").unwrap();
+        let posterior = estimate_bag(&games, &Priors { max: 5, ..Priors::default() }).unwrap();
+        assert_eq!(posterior.map, Bag { red: 5, green: 0, blue: 0 });
+        assert_eq!((posterior.red.mode, posterior.red.lower, posterior.red.upper), (5, 5, 5));
+        assert_eq!(posterior.red.mean, 5.0);
+        assert_eq!(posterior.blue.mode, 0);
+    }
+
+    #[test]
+    fn test_estimate_repeated_colour() {
+        let games = parse_games(b"Game 1: 3 red, 2 red").unwrap();
+        let posterior = estimate_bag(&games, &Priors { max: 5, ..Priors::default() }).unwrap();
+        assert_eq!(posterior.map, Bag { red: 5, green: 0, blue: 0 });
+        assert_eq!(estimate_bag(&games, &Priors { max: 4, ..Priors::default() }), None);
+    }
+
+    #[test]
+    fn test_estimate_respects_observed_maxima() {
+        let games = parse_games(include_bytes!("test-input.txt")).unwrap();
+        let posterior = estimate_bag(&games, &Priors::default()).unwrap();
+        assert!(posterior.map.red >= 20 && posterior.map.green >= 13 && posterior.map.blue >= 15);
+        assert!(posterior.red.lower >= 20 && posterior.red.upper <= 30);
+        assert!(posterior.red.mean >= 20.0);
+
+        assert_eq!(estimate_bag(&games, &Priors { max: 19, ..Priors::default() }), None);
+        assert_eq!(estimate_bag(&games, &Priors { max: MAX_COUNT + 1, ..Priors::default() }), None);
+        assert_eq!(estimate_bag(&games, &Priors { max: u64::MAX, ..Priors::default() }), None);
+    }
+
+    #[test]
+    fn test_estimate_follows_prior() {
+        let games = parse_games(b"Game 1: 1 red, 1 green, 1 blue").unwrap();
+        let priors = Priors {
+            red: Prior::Poisson(4.0),
+            green: Prior::Poisson(20.0),
+            blue: Prior::Uniform,
+            max: 40,
+        };
+        let posterior = estimate_bag(&games, &priors).unwrap();
+        assert!(posterior.red.mean < posterior.green.mean);
+        assert!(posterior.red.mean > 1.0);
+    }
+
+    #[test]
+    fn test_parse_prior() {
+        assert_eq!(parse_prior("uniform"), Ok(Prior::Uniform));
+        assert_eq!(parse_prior("poisson:12.5"), Ok(Prior::Poisson(12.5)));
+        assert!(parse_prior("poisson:-1").is_err());
+        assert!(parse_prior("normal").is_err());
+    }
+}
@@ -0,0 +1,139 @@
+//! Writes parsed games as CSV or JSON for spreadsheets and dataframes.
+//!
+//! CSV has one row per cube, and repeats the minimum set, power and possible flag of the game
+//! on each of its rows. An empty set is written as a row without a cube, and a game without
+//! sets as a row without a set, so the CSV input format reads back exactly the games written.
+//!
+//! JSON has one object per game, with every set as an object from colour to count, in the
+//! shape the JSON input format reads. That shape cannot list a colour twice or keep the order
+//! of the cubes, so a colour repeated within a set is written as its total and the cubes are
+//! read back in red, green, blue order.
+
+use crate::{calc_set_power, determine_minimum_cubes, is_game_possible_in, Bag, Cube, Game};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io::Write;
+
+#[derive(Serialize)]
+struct CubeRow {
+    game: u64,
+    set: Option<usize>,
+    cube: Option<usize>,
+    color: Option<String>,
+    count: Option<u64>,
+    min_red: u64,
+    min_green: u64,
+    min_blue: u64,
+    power: u64,
+    possible: bool,
+}
+
+#[derive(Serialize)]
+struct GameRecord {
+    id: u64,
+    sets: Vec<BTreeMap<String, u64>>,
+    minimum_set: Bag,
+    power: u64,
+    possible: bool,
+}
+
+/// Writes one CSV row per cube, judging possible games against `bag`.
+pub fn write_csv<W: Write>(games: &[Game], bag: &Bag, writer: W) -> csv::Result<()> {
+    let mut writer = csv::Writer::from_writer(writer);
+    for game in games {
+        let minimum_set = determine_minimum_cubes(game);
+        let power = calc_set_power(&minimum_set);
+        let possible = is_game_possible_in(game, bag);
+        let row = |set: Option<usize>, cube: Option<(usize, &Cube)>| CubeRow {
+            game: game.number,
+            set,
+            cube: cube.map(|(cube_index, _)| cube_index + 1),
+            color: cube.map(|(_, cube)| cube.color.to_string()),
+            count: cube.map(|(_, cube)| cube.count),
+            min_red: minimum_set.red.count,
+            min_green: minimum_set.green.count,
+            min_blue: minimum_set.blue.count,
+            power,
+            possible,
+        };
+        if game.sets.is_empty() {
+            writer.serialize(row(None, None))?;
+        }
+        for (set_index, set) in game.sets.iter().enumerate() {
+            if set.cubes.is_empty() {
+                writer.serialize(row(Some(set_index + 1), None))?;
+            }
+            for cube in set.cubes.iter().enumerate() {
+                writer.serialize(row(Some(set_index + 1), Some(cube)))?;
+            }
+        }
+    }
+    writer.flush()?;
+    return Ok(());
+}
+
+/// Writes the games as a JSON array, judging possible games against `bag`.
+pub fn write_json<W: Write>(games: &[Game], bag: &Bag, writer: W) -> serde_json::Result<()> {
+    let records = games.iter().map(|game| {
+        let minimum_set = determine_minimum_cubes(game);
+        GameRecord {
+            id: game.number,
+            sets: game.sets.iter().map(|set| {
+                let mut counts = BTreeMap::new();
+                for cube in &set.cubes {
+                    *counts.entry(cube.color.to_string()).or_insert(0) += cube.count;
+                }
+                counts
+            }).collect(),
+            minimum_set: Bag {
+                red: minimum_set.red.count,
+                green: minimum_set.green.count,
+                blue: minimum_set.blue.count,
+            },
+            power: calc_set_power(&minimum_set),
+            possible: is_game_possible_in(game, bag),
+        }
+    }).collect::<Vec<GameRecord>>();
+    return serde_json::to_writer_pretty(writer, &records);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_games;
+
+    #[test]
+    fn test_write_csv() {
+        let mut games = parse_games(b"Game 1: 3 blue, 4 red; ; 2 green\nGame 3: 20 red").unwrap();
+        games.push(Game { number: 4, sets: vec![] });
+        let mut output = Vec::new();
+        write_csv(&games, &Bag::default(), &mut output).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "game,set,cube,color,count,min_red,min_green,min_blue,power,possible\n\
+             1,1,1,blue,3,4,2,3,24,true\n\
+             1,1,2,red,4,4,2,3,24,true\n\
+             1,2,,,,4,2,3,24,true\n\
+             1,3,1,green,2,4,2,3,24,true\n\
+             3,1,1,red,20,20,0,0,0,false\n\
+             4,,,,,0,0,0,0,true\n"
+        );
+    }
+
+    #[test]
+    fn test_write_json() {
+        let games = parse_games(b"Game 1: 3 blue, 4 red; 2 green").unwrap();
+        let mut output = Vec::new();
+        write_json(&games, &Bag { red: 3, green: 13, blue: 14 }, &mut output).unwrap();
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&output).unwrap(),
+            serde_json::json!([{
+                "id": 1,
+                "sets": [{ "blue": 3, "red": 4 }, { "green": 2 }],
+                "minimum_set": { "red": 4, "green": 2, "blue": 3 },
+                "power": 24,
+                "possible": false,
+            }])
+        );
+    }
+}
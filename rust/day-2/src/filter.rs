@@ -0,0 +1,420 @@
+//! A small expression language for selecting games, such as
+//! `max(red) > 10 and any_set(blue >= 5 and green == 0)`.
+//!
+//! Expressions combine comparisons with `and`, `or`, `not` and parentheses. What a name
+//! means depends on where it is used:
+//!
+//! - on a game: `id`, `sets`, `power`, and `max(colour)`, `min(colour)` and `sum(colour)`
+//!   over the counts of a colour in its sets, where a set without the colour counts as 0.
+//! - inside `any_set(...)` or `all_sets(...)`: additionally `red`, `green` and `blue`, the
+//!   counts in that set, and `cubes`, the number of cubes in the set.
+//! - inside `any_cube(...)` or `all_cubes(...)` of a set: additionally `count` and `color`,
+//!   which compares against the colours `red`, `green` and `blue`.
+
+use crate::{calc_set_power, determine_minimum_cubes, Color, Cube, Game, Set};
+use std::error::Error;
+use std::fmt;
+
+/// A parsed filter expression.
+#[derive(PartialEq, Debug, Clone)]
+pub struct Filter {
+    expression: Expression,
+}
+
+/// Why an expression could not be parsed, with the 1-based column it was noticed at.
+#[derive(PartialEq, Debug, Clone)]
+pub struct FilterError {
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for FilterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return write!(f, "column {}: {}", self.column, self.message);
+    }
+}
+
+impl Error for FilterError {}
+
+#[derive(PartialEq, Debug, Clone)]
+enum Expression {
+    Or(Box<Expression>, Box<Expression>),
+    And(Box<Expression>, Box<Expression>),
+    Not(Box<Expression>),
+    Compare(Operand, Comparison, Operand),
+    AnySet(Box<Expression>),
+    AllSets(Box<Expression>),
+    AnyCube(Box<Expression>),
+    AllCubes(Box<Expression>),
+}
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+#[derive(PartialEq, Debug, Clone)]
+enum Operand {
+    Number(u64),
+    Color(Color),
+    Id,
+    Sets,
+    Power,
+    Max(Color),
+    Min(Color),
+    Sum(Color),
+    SetCount(Color),
+    SetCubes,
+    CubeCount,
+    CubeColor,
+}
+
+#[derive(PartialEq, Debug, Clone, Copy, PartialOrd)]
+enum Scope {
+    Game,
+    Set,
+    Cube,
+}
+
+#[derive(PartialEq, Debug, Clone)]
+enum Token {
+    Number(u64),
+    Name(String),
+    Comparison(Comparison),
+    Open,
+    Close,
+}
+
+#[derive(PartialEq, Debug, Clone)]
+enum Value {
+    Number(u64),
+    Color(Color),
+}
+
+impl Filter {
+    pub fn parse(text: &str) -> Result<Filter, FilterError> {
+        let mut parser = Parser { tokens: tokenize(text)?, position: 0, end: text.chars().count() + 1 };
+        let expression = parser.or(Scope::Game)?;
+        if let Some((column, token)) = parser.tokens.get(parser.position) {
+            return Err(FilterError { column: *column, message: format!("unexpected {:?}", token) });
+        }
+        return Ok(Filter { expression });
+    }
+
+    pub fn matches(&self, game: &Game) -> bool {
+        return evaluate(&self.expression, &Context { game, set: None, cube: None });
+    }
+}
+
+fn tokenize(text: &str) -> Result<Vec<(usize, Token)>, FilterError> {
+    let characters = text.chars().collect::<Vec<char>>();
+    let mut tokens = Vec::new();
+    let mut index = 0;
+    while index < characters.len() {
+        let column = index + 1;
+        let character = characters[index];
+        let next = characters.get(index + 1).copied();
+        let (token, length) = match (character, next) {
+            (character, _) if character.is_whitespace() => {
+                index += 1;
+                continue;
+            }
+            ('(', _) => (Token::Open, 1),
+            (')', _) => (Token::Close, 1),
+            ('=', Some('=')) => (Token::Comparison(Comparison::Equal), 2),
+            ('!', Some('=')) => (Token::Comparison(Comparison::NotEqual), 2),
+            ('<', Some('=')) => (Token::Comparison(Comparison::LessOrEqual), 2),
+            ('>', Some('=')) => (Token::Comparison(Comparison::GreaterOrEqual), 2),
+            ('<', _) => (Token::Comparison(Comparison::Less), 1),
+            ('>', _) => (Token::Comparison(Comparison::Greater), 1),
+            (character, _) if character.is_ascii_digit() || character.is_alphabetic() || character == '_' => {
+                let length = characters[index..]
+                    .iter()
+                    .take_while(|&&character| character.is_alphanumeric() || character == '_')
+                    .count();
+                let word = characters[index..index + length].iter().collect::<String>();
+                let token = if character.is_ascii_digit() {
+                    Token::Number(word.parse().map_err(|_| FilterError { column, message: format!("invalid number {:?}", word) })?)
+                } else {
+                    Token::Name(word)
+                };
+                (token, length)
+            }
+            (character, _) => return Err(FilterError { column, message: format!("unexpected {:?}", character) }),
+        };
+        tokens.push((column, token));
+        index += length;
+    }
+    return Ok(tokens);
+}
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    position: usize,
+    // Column reported for errors at the end of the expression.
+    end: usize,
+}
+
+impl Parser {
+    fn or(&mut self, scope: Scope) -> Result<Expression, FilterError> {
+        let mut expression = self.and(scope)?;
+        while self.accept_name("or") {
+            expression = Expression::Or(Box::new(expression), Box::new(self.and(scope)?));
+        }
+        return Ok(expression);
+    }
+
+    fn and(&mut self, scope: Scope) -> Result<Expression, FilterError> {
+        let mut expression = self.not(scope)?;
+        while self.accept_name("and") {
+            expression = Expression::And(Box::new(expression), Box::new(self.not(scope)?));
+        }
+        return Ok(expression);
+    }
+
+    fn not(&mut self, scope: Scope) -> Result<Expression, FilterError> {
+        if self.accept_name("not") {
+            return Ok(Expression::Not(Box::new(self.not(scope)?)));
+        }
+        if self.peek() == Some(&Token::Open) {
+            self.position += 1;
+            let expression = self.or(scope)?;
+            self.expect(Token::Close)?;
+            return Ok(expression);
+        }
+        let quantifier = match self.peek() {
+            Some(Token::Name(name)) => match name.as_str() {
+                "any_set" | "all_sets" => Some((name.clone(), Scope::Game, Scope::Set)),
+                "any_cube" | "all_cubes" => Some((name.clone(), Scope::Set, Scope::Cube)),
+                _ => None,
+            },
+            _ => None,
+        };
+        if let Some((name, outer, inner)) = quantifier {
+            if scope != outer {
+                return Err(self.error(format!("{} can only be used {}", name, if outer == Scope::Game { "on a game" } else { "inside a set" })));
+            }
+            self.position += 1;
+            self.expect(Token::Open)?;
+            let body = Box::new(self.or(inner)?);
+            self.expect(Token::Close)?;
+            return Ok(match name.as_str() {
+                "any_set" => Expression::AnySet(body),
+                "all_sets" => Expression::AllSets(body),
+                "any_cube" => Expression::AnyCube(body),
+                _ => Expression::AllCubes(body),
+            });
+        }
+
+        let left = self.operand(scope)?;
+        let comparison = match self.peek() {
+            Some(&Token::Comparison(comparison)) => comparison,
+            _ => return Err(self.error("expected a comparison".to_string())),
+        };
+        self.position += 1;
+        let right_column = self.column();
+        let right = self.operand(scope)?;
+        let is_color = |operand: &Operand| matches!(operand, Operand::Color(_) | Operand::CubeColor);
+        if is_color(&left) != is_color(&right) {
+            return Err(FilterError { column: right_column, message: "cannot compare a colour with a number".to_string() });
+        }
+        if is_color(&left) && !matches!(comparison, Comparison::Equal | Comparison::NotEqual) {
+            return Err(FilterError { column: right_column, message: "colours can only be compared with == and !=".to_string() });
+        }
+        return Ok(Expression::Compare(left, comparison, right));
+    }
+
+    fn operand(&mut self, scope: Scope) -> Result<Operand, FilterError> {
+        let column = self.column();
+        let name = match self.tokens.get(self.position) {
+            Some((_, Token::Number(number))) => {
+                self.position += 1;
+                return Ok(Operand::Number(*number));
+            }
+            Some((_, Token::Name(name))) => name.clone(),
+            _ => return Err(self.error("expected a number or a name".to_string())),
+        };
+        self.position += 1;
+
+        if let "max" | "min" | "sum" = name.as_str() {
+            self.expect(Token::Open)?;
+            let color = match self.tokens.get(self.position).map(|(_, token)| token) {
+                Some(Token::Name(color)) => parse_color(color),
+                _ => None,
+            }.ok_or(self.error("expected red, green or blue".to_string()))?;
+            self.position += 1;
+            self.expect(Token::Close)?;
+            return Ok(match name.as_str() {
+                "max" => Operand::Max(color),
+                "min" => Operand::Min(color),
+                _ => Operand::Sum(color),
+            });
+        }
+
+        let operand = match (name.as_str(), scope) {
+            ("id", _) => Operand::Id,
+            ("sets", _) => Operand::Sets,
+            ("power", _) => Operand::Power,
+            ("count", Scope::Cube) => Operand::CubeCount,
+            ("color", Scope::Cube) => Operand::CubeColor,
+            (color, Scope::Cube) if parse_color(color).is_some() => Operand::Color(parse_color(color).unwrap()),
+            (color, Scope::Set) if parse_color(color).is_some() => Operand::SetCount(parse_color(color).unwrap()),
+            ("cubes", Scope::Set | Scope::Cube) => Operand::SetCubes,
+            _ => return Err(FilterError { column, message: format!("unknown name {:?} here", name) }),
+        };
+        return Ok(operand);
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        return self.tokens.get(self.position).map(|(_, token)| token);
+    }
+
+    fn accept_name(&mut self, keyword: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Name(name)) if name == keyword) {
+            self.position += 1;
+            return true;
+        }
+        return false;
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), FilterError> {
+        if self.peek() != Some(&expected) {
+            return Err(self.error(format!("expected {}", if expected == Token::Open { "'('" } else { "')'" })));
+        }
+        self.position += 1;
+        return Ok(());
+    }
+
+    fn column(&self) -> usize {
+        return self.tokens.get(self.position).map_or(self.end, |(column, _)| *column);
+    }
+
+    fn error(&self, message: String) -> FilterError {
+        return FilterError { column: self.column(), message };
+    }
+}
+
+fn parse_color(name: &str) -> Option<Color> {
+    return match name {
+        "red" => Some(Color::RED),
+        "green" => Some(Color::GREEN),
+        "blue" => Some(Color::BLUE),
+        _ => None,
+    };
+}
+
+struct Context<'a> {
+    game: &'a Game,
+    set: Option<&'a Set>,
+    cube: Option<&'a Cube>,
+}
+
+fn evaluate(expression: &Expression, context: &Context) -> bool {
+    let in_set = |set| Context { game: context.game, set: Some(set), cube: None };
+    let in_cube = |cube| Context { game: context.game, set: context.set, cube: Some(cube) };
+    return match expression {
+        Expression::Or(left, right) => evaluate(left, context) || evaluate(right, context),
+        Expression::And(left, right) => evaluate(left, context) && evaluate(right, context),
+        Expression::Not(inner) => !evaluate(inner, context),
+        Expression::AnySet(inner) => context.game.sets.iter().any(|set| evaluate(inner, &in_set(set))),
+        Expression::AllSets(inner) => context.game.sets.iter().all(|set| evaluate(inner, &in_set(set))),
+        Expression::AnyCube(inner) => context.set.unwrap().cubes.iter().any(|cube| evaluate(inner, &in_cube(cube))),
+        Expression::AllCubes(inner) => context.set.unwrap().cubes.iter().all(|cube| evaluate(inner, &in_cube(cube))),
+        Expression::Compare(left, comparison, right) => {
+            let (left, right) = (value(left, context), value(right, context));
+            match comparison {
+                Comparison::Equal => left == right,
+                Comparison::NotEqual => left != right,
+                _ => {
+                    let (Value::Number(left), Value::Number(right)) = (left, right) else {
+                        unreachable!("colours are only compared for equality")
+                    };
+                    match comparison {
+                        Comparison::Less => left < right,
+                        Comparison::LessOrEqual => left <= right,
+                        Comparison::Greater => left > right,
+                        _ => left >= right,
+                    }
+                }
+            }
+        }
+    };
+}
+
+fn value(operand: &Operand, context: &Context) -> Value {
+    let game = context.game;
+    let set_count = |set: &Set, color: &Color| set.cubes.iter()
+        .filter(|cube| &cube.color == color)
+        .map(|cube| cube.count)
+        .sum::<u64>();
+    return Value::Number(match operand {
+        Operand::Number(number) => *number,
+        Operand::Color(color) => return Value::Color(color.clone()),
+        Operand::CubeColor => return Value::Color(context.cube.unwrap().color.clone()),
+        Operand::Id => game.number,
+        Operand::Sets => game.sets.len() as u64,
+        Operand::Power => calc_set_power(&determine_minimum_cubes(game)),
+        Operand::Max(color) => game.sets.iter().map(|set| set_count(set, color)).max().unwrap_or(0),
+        Operand::Min(color) => game.sets.iter().map(|set| set_count(set, color)).min().unwrap_or(0),
+        Operand::Sum(color) => game.sets.iter().map(|set| set_count(set, color)).sum(),
+        Operand::SetCount(color) => set_count(context.set.unwrap(), color),
+        Operand::SetCubes => context.set.unwrap().cubes.iter().map(|cube| cube.count).sum(),
+        Operand::CubeCount => context.cube.unwrap().count,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_games, summarize};
+
+    fn selected(expression: &str) -> Vec<u64> {
+        let filter = Filter::parse(expression).unwrap();
+        return parse_games(include_bytes!("test-input.txt")).unwrap()
+            .into_iter()
+            .filter(|game| filter.matches(game))
+            .map(|game| game.number)
+            .collect();
+    }
+
+    #[test]
+    fn test_filter_games() {
+        assert_eq!(selected("max(red) > 10 and any_set(blue >= 5 and green == 0)"), Vec::<u64>::new());
+        assert_eq!(selected("max(red) > 10 and any_set(blue >= 5)"), vec![3, 4]);
+        assert_eq!(selected("all_sets(cubes <= 9)"), vec![1, 2]);
+        assert_eq!(selected("sum(green) == 6 or not (id < 5)"), vec![2, 5]);
+        assert_eq!(selected("sets == 3 and min(blue) >= 1 and power < 100"), vec![2]);
+        assert_eq!(selected("any_set(any_cube(color == red and count >= 14))"), vec![3, 4]);
+        assert_eq!(selected("any_set(all_cubes(color != blue) and id > 2)"), vec![3, 4]);
+    }
+
+    #[test]
+    fn test_filtered_summary() {
+        let filter = Filter::parse("max(green) < 5").unwrap();
+        let games = parse_games(include_bytes!("test-input.txt")).unwrap()
+            .into_iter()
+            .filter(|game| filter.matches(game))
+            .collect::<Vec<Game>>();
+        assert_eq!(summarize(&games).id_sum, 1 + 2 + 5);
+        assert_eq!(summarize(&games).power_sum, 48 + 12 + 630 + 36);
+    }
+
+    #[test]
+    fn test_filter_errors() {
+        let error = |expression| Filter::parse(expression).unwrap_err();
+        assert_eq!(error("red > 1"), FilterError { column: 1, message: "unknown name \"red\" here".to_string() });
+        assert_eq!(error("max(purple) > 1").column, 5);
+        assert_eq!(error("id >").column, 5);
+        assert_eq!(error("any_set(color == red)").column, 9);
+        assert_eq!(error("any_set(any_cube(color > red))").message, "colours can only be compared with == and !=");
+        assert_eq!(error("any_cube(count > 1)").message, "any_cube can only be used inside a set");
+        assert_eq!(error("id == 1 id").column, 9);
+        assert_eq!(error("(id == 1").message, "expected ')'");
+        assert_eq!(error("id # 1").column, 4);
+    }
+}
@@ -0,0 +1,145 @@
+//! Incremental evaluation of a game log that is still being written, like `tail -f`.
+
+use crate::{parse_game_bytes, summarize_in, Bag, Game, ParseError, Summary};
+use aoc::input_file::LineError;
+use aoc::normalize::{is_blank, strip_bom, InvalidUtf8};
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::slice;
+
+/// Follows a game log, folding every newly completed line into running answers.
+///
+/// Only the bytes appended since the previous poll are read, so earlier games are never
+/// parsed again. A line is evaluated once its newline has been written. If the log shrinks,
+/// it is assumed to have been truncated and is followed again from the start.
+pub struct Follower {
+    path: PathBuf,
+    bag: Bag,
+    invalid_utf8: InvalidUtf8,
+    offset: u64,
+    pending: Vec<u8>,
+    line_number: usize,
+    games: usize,
+    summary: Summary,
+}
+
+impl Follower {
+    pub fn new<P: AsRef<Path>>(path: P, bag: Bag, invalid_utf8: InvalidUtf8) -> Follower {
+        return Follower {
+            path: path.as_ref().to_path_buf(),
+            bag,
+            invalid_utf8,
+            offset: 0,
+            pending: Vec::new(),
+            line_number: 0,
+            games: 0,
+            summary: Summary { id_sum: 0, power_sum: 0 },
+        };
+    }
+
+    /// Reads the lines completed since the last poll and returns the games parsed from them.
+    pub fn poll(&mut self) -> io::Result<Vec<Result<Game, LineError<ParseError>>>> {
+        let mut file = File::open(&self.path)?;
+        let length = file.metadata()?.len();
+        if length < self.offset {
+            *self = Follower::new(&self.path, self.bag, self.invalid_utf8);
+        }
+
+        file.seek(SeekFrom::Start(self.offset))?;
+        let read = file.read_to_end(&mut self.pending)?;
+        self.offset += read as u64;
+
+        let complete = match self.pending.iter().rposition(|&byte| byte == b'\n') {
+            Some(position) => position + 1,
+            None => return Ok(Vec::new()),
+        };
+        let lines = self.pending.drain(..complete).collect::<Vec<u8>>();
+        let mut games = Vec::new();
+        for line in lines[..complete - 1].split(|&byte| byte == b'\n') {
+            self.line_number += 1;
+            let line = line.strip_suffix(b"\r").unwrap_or(line);
+            let line = if self.line_number == 1 { strip_bom(line) } else { line };
+            if is_blank(line) {
+                continue;
+            }
+            let game = parse_game_bytes(&self.invalid_utf8.apply(line)).map_err(|error| LineError { line: self.line_number, error });
+            if let Ok(game) = &game {
+                self.add(game);
+            }
+            games.push(game);
+        }
+        return Ok(games);
+    }
+
+    /// The answers over every game seen so far.
+    pub fn summary(&self) -> &Summary {
+        return &self.summary;
+    }
+
+    /// The number of games seen so far.
+    pub fn games(&self) -> usize {
+        return self.games;
+    }
+
+    fn add(&mut self, game: &Game) {
+        self.games += 1;
+        let summary = summarize_in(slice::from_ref(game), &self.bag);
+        self.summary.id_sum += summary.id_sum;
+        self.summary.power_sum += summary.power_sum;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timing::stages;
+    use std::env;
+    use std::fs::{self, OpenOptions};
+    use std::io::Write;
+
+    fn append(path: &Path, text: &str) {
+        OpenOptions::new().append(true).open(path).unwrap().write_all(text.as_bytes()).unwrap();
+    }
+
+    #[test]
+    fn test_follow_growing_log() {
+        let path = env::temp_dir().join(format!("day-2-follow-{}.txt", std::process::id()));
+        fs::write(&path, "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green\nGame 2: 1 blue, 2 gr").unwrap();
+        let mut follower = Follower::new(&path, Bag::default(), InvalidUtf8::Report);
+
+        assert_eq!(follower.poll().unwrap().len(), 1);
+        assert_eq!(follower.summary(), &Summary { id_sum: 1, power_sum: 48 });
+
+        append(&path, "een; 3 green, 4 blue, 1 red; 1 green, 1 blue\r\nGame 3: 8 green, 6 blue, 20 red\n\nbroken\n");
+        let polled = follower.poll().unwrap();
+        assert_eq!(polled.len(), 3);
+        assert_eq!(polled[2], Err(LineError { line: 5, error: ParseError::MissingSeparator("broken".to_string()) }));
+        assert_eq!(follower.games(), 3);
+        assert_eq!(follower.summary(), &Summary { id_sum: 3, power_sum: 48 + 12 + 960 });
+
+        assert_eq!(follower.poll().unwrap().len(), 0);
+
+        fs::write(&path, "Game 7: 2 red\n").unwrap();
+        follower.poll().unwrap();
+        assert_eq!(follower.games(), 1);
+        assert_eq!(follower.summary(), &Summary { id_sum: 7, power_sum: 0 });
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_polls_keep_no_times() {
+        let path = env::temp_dir().join(format!("day-2-follow-times-{}.txt", std::process::id()));
+        fs::write(&path, "").unwrap();
+        let mut follower = Follower::new(&path, Bag::default(), InvalidUtf8::Report);
+        for number in 1..=100 {
+            append(&path, &format!("Game {}: 3 blue, 4 red\n", number));
+            follower.poll().unwrap();
+        }
+        assert_eq!(follower.games(), 100);
+        for (name, key) in stages() {
+            assert_eq!(key.with(|times| times.borrow().len()), 0, "{}", name);
+        }
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,200 @@
+//! Inverse questions about bags: which bags make enough games possible, which bag is the
+//! cheapest for a chosen set of games, and how little a bag has to grow to admit another
+//! game. All of them work on the minimum sets of the games, since a game is possible exactly
+//! when its minimum set fits in the bag.
+
+use crate::{determine_minimum_cubes, Bag, Game, MinimumSet};
+use std::collections::BinaryHeap;
+
+/// The growth of a bag that makes `game` possible with as few additional cubes as possible.
+#[derive(PartialEq, Debug, Clone)]
+pub struct BagChange {
+    pub game: u64,
+    pub bag: Bag,
+    /// Wide enough for a game that needs almost `u64::MAX` cubes of every colour.
+    pub added: u128,
+}
+
+/// The bags that make at least `k` games possible and cannot lose a single cube without
+/// falling below `k`, ordered by red, then green, then blue. Empty if the log has fewer
+/// than `k` games.
+pub fn pareto_frontier(games: &[Game], k: usize) -> Vec<Bag> {
+    let minimum_sets = games.iter().map(determine_minimum_cubes).collect::<Vec<MinimumSet>>();
+    if k == 0 {
+        return vec![Bag { red: 0, green: 0, blue: 0 }];
+    }
+
+    // Every bag on the frontier only holds as many cubes of a colour as some game needs, and
+    // the fewest blue cubes for given red and green limits is the k-th smallest blue count of
+    // the games within them. That count only falls as either limit grows, so a bag is on the
+    // frontier exactly when it needs fewer blue cubes than with the next lower red or green.
+    let reds = distinct(minimum_sets.iter().map(|set| set.red.count));
+    let greens = distinct(minimum_sets.iter().map(|set| set.green.count));
+    let mut points = minimum_sets.iter()
+        .map(|set| (set.green.count, set.red.count, set.blue.count))
+        .collect::<Vec<(u64, u64, u64)>>();
+    points.sort_unstable();
+
+    let mut frontier = Vec::new();
+    let mut lower_red: Vec<Option<u64>> = vec![None; greens.len()];
+    for &red in &reds {
+        // The k smallest blue counts so far, with the largest of them on top.
+        let mut smallest = BinaryHeap::with_capacity(k + 1);
+        let mut next = 0;
+        let mut lower_green = None;
+        for (index, &green) in greens.iter().enumerate() {
+            while next < points.len() && points[next].0 <= green {
+                if points[next].1 <= red {
+                    smallest.push(points[next].2);
+                    if smallest.len() > k {
+                        smallest.pop();
+                    }
+                }
+                next += 1;
+            }
+            let blue = if smallest.len() == k { smallest.peek().copied() } else { None };
+            if let Some(blue) = blue {
+                let fewer = |lower: Option<u64>| lower.is_none_or(|lower| blue < lower);
+                if fewer(lower_red[index]) && fewer(lower_green) {
+                    frontier.push(Bag { red, green, blue });
+                }
+            }
+            lower_red[index] = blue;
+            lower_green = blue;
+        }
+    }
+    return frontier;
+}
+
+/// The bag with the fewest cubes that makes every game in `ids` possible, or `None` if one
+/// of the ids is not in the log.
+pub fn cheapest_bag(games: &[Game], ids: &[u64]) -> Option<Bag> {
+    let mut bag = Bag { red: 0, green: 0, blue: 0 };
+    for id in ids {
+        let minimum_set = determine_minimum_cubes(games.iter().find(|game| game.number == *id)?);
+        bag.red = bag.red.max(minimum_set.red.count);
+        bag.green = bag.green.max(minimum_set.green.count);
+        bag.blue = bag.blue.max(minimum_set.blue.count);
+    }
+    return Some(bag);
+}
+
+/// The smallest growth of `bag`, counted in added cubes, that makes one more game possible.
+/// Ties go to the game with the lowest id. `None` if every game is already possible.
+pub fn smallest_change(games: &[Game], bag: &Bag) -> Option<BagChange> {
+    return games.iter()
+        .map(|game| {
+            let minimum_set = determine_minimum_cubes(game);
+            let grown = Bag {
+                red: bag.red.max(minimum_set.red.count),
+                green: bag.green.max(minimum_set.green.count),
+                blue: bag.blue.max(minimum_set.blue.count),
+            };
+            let added = (grown.red - bag.red) as u128 + (grown.green - bag.green) as u128 + (grown.blue - bag.blue) as u128;
+            BagChange { game: game.number, bag: grown, added }
+        })
+        .filter(|change| change.added > 0)
+        .min_by_key(|change| (change.added, change.game));
+}
+
+fn distinct(counts: impl Iterator<Item = u64>) -> Vec<u64> {
+    let mut counts = counts.collect::<Vec<u64>>();
+    counts.sort_unstable();
+    counts.dedup();
+    return counts;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulate::{simulate, Replacement, Simulation};
+    use crate::{is_game_possible_in, parse_games};
+
+    fn test_games() -> Vec<Game> {
+        return parse_games(include_bytes!("test-input.txt")).unwrap();
+    }
+
+    #[test]
+    fn test_pareto_frontier() {
+        let games = test_games();
+        assert_eq!(
+            pareto_frontier(&games, 4),
+            vec![
+                Bag { red: 14, green: 3, blue: 15 },
+                Bag { red: 20, green: 13, blue: 6 },
+            ]
+        );
+        assert_eq!(pareto_frontier(&games, 5), vec![Bag { red: 20, green: 13, blue: 15 }]);
+        assert_eq!(pareto_frontier(&games, 6), vec![]);
+
+        for k in 1..=5 {
+            let frontier = pareto_frontier(&games, k);
+            for bag in frontier {
+                let possible = |bag: &Bag| games.iter().filter(|game| is_game_possible_in(game, bag)).count();
+                assert!(possible(&bag) >= k);
+                for smaller in [
+                    Bag { red: bag.red.saturating_sub(1), ..bag },
+                    Bag { green: bag.green.saturating_sub(1), ..bag },
+                    Bag { blue: bag.blue.saturating_sub(1), ..bag },
+                ] {
+                    assert!(smaller == bag || possible(&smaller) < k, "{} is not minimal", bag);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_pareto_frontier_matches_every_candidate() {
+        let simulation = Simulation {
+            bag: Bag { red: 9, green: 8, blue: 10 },
+            games: 60,
+            sets: 1..=3,
+            handful: 1..=8,
+            replacement: Replacement::With,
+            seed: 7,
+        };
+        let games = simulate(&simulation);
+        let minimum_sets = games.iter().map(determine_minimum_cubes).collect::<Vec<MinimumSet>>();
+        let possible = |bag: &Bag| minimum_sets.iter()
+            .filter(|set| set.red.count <= bag.red && set.green.count <= bag.green && set.blue.count <= bag.blue)
+            .count();
+        for k in [1, 5, 30, 60] {
+            let mut expected = Vec::new();
+            for red in 0..=9 {
+                for green in 0..=8 {
+                    for blue in 0..=10 {
+                        let bag = Bag { red, green, blue };
+                        let minimal = [Bag { red: red.wrapping_sub(1), ..bag }, Bag { green: green.wrapping_sub(1), ..bag }, Bag { blue: blue.wrapping_sub(1), ..bag }]
+                            .iter()
+                            .all(|smaller| smaller.red > red || smaller.green > green || smaller.blue > blue || possible(smaller) < k);
+                        if possible(&bag) >= k && minimal {
+                            expected.push(bag);
+                        }
+                    }
+                }
+            }
+            assert_eq!(pareto_frontier(&games, k), expected, "k = {}", k);
+        }
+    }
+
+    #[test]
+    fn test_cheapest_bag() {
+        let games = test_games();
+        assert_eq!(cheapest_bag(&games, &[1, 2, 5]), Some(Bag { red: 6, green: 3, blue: 6 }));
+        assert_eq!(cheapest_bag(&games, &[]), Some(Bag { red: 0, green: 0, blue: 0 }));
+        assert_eq!(cheapest_bag(&games, &[1, 9]), None);
+    }
+
+    #[test]
+    fn test_smallest_change() {
+        let games = test_games();
+        assert_eq!(
+            smallest_change(&games, &Bag::default()),
+            Some(BagChange { game: 4, bag: Bag { red: 14, green: 13, blue: 15 }, added: 3 })
+        );
+        assert_eq!(smallest_change(&games, &Bag { red: 20, green: 13, blue: 15 }), None);
+
+        let huge = parse_games(b"Game 1: 18446744073709551615 red, 18446744073709551615 green, 18446744073709551615 blue").unwrap();
+        assert_eq!(smallest_change(&huge, &Bag::default()).unwrap().added, 3 * u64::MAX as u128 - 39);
+    }
+}
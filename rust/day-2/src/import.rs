@@ -0,0 +1,295 @@
+//! Reads game logs that are not in the puzzle's text format.
+//!
+//! JSON logs are an array of games such as `{"id": 1, "sets": [{"red": 4, "blue": 3}]}`,
+//! where other fields of a game are ignored. CSV logs have a header and one row per cube with
+//! at least the columns `game`, `set`, `color` and `count`, and consecutive rows of the same
+//! game and set form one set. A row with an empty `set` is a game without sets, and a row with
+//! an empty `color` and `count` is an empty set. A row of the same game whose `set` goes back,
+//! or whose optional `cube` column does not go forward within a set, starts another game with
+//! that id, so a listed id is kept apart rather than merged. Both are the shapes the exports
+//! write, so exported logs can be read back.
+
+use crate::{parse_color, parse_games_by_line, Color, Cube, Game, ParseError, Set};
+use aoc::input_file::LineError;
+use aoc::normalize::{strip_bom, InvalidUtf8};
+use serde::Deserialize;
+use serde_json::value::RawValue;
+use std::path::Path;
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum InputFormat {
+    Text,
+    Json,
+    Csv,
+}
+
+impl InputFormat {
+    /// The format implied by the extension of `path`, text unless it is `.json` or `.csv`.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> InputFormat {
+        return match path.as_ref().extension().and_then(|extension| extension.to_str()) {
+            Some("json") => InputFormat::Json,
+            Some("csv") => InputFormat::Csv,
+            _ => InputFormat::Text,
+        };
+    }
+}
+
+/// Parses a format name: `text`, `json` or `csv`.
+pub fn parse_input_format(text: &str) -> Result<InputFormat, String> {
+    return match text {
+        "text" => Ok(InputFormat::Text),
+        "json" => Ok(InputFormat::Json),
+        "csv" => Ok(InputFormat::Csv),
+        _ => Err(format!("unknown input format {:?}, expected text, json or csv", text)),
+    };
+}
+
+#[derive(Deserialize)]
+struct JsonGame {
+    id: u64,
+    sets: Vec<JsonSet>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JsonSet {
+    red: Option<u64>,
+    green: Option<u64>,
+    blue: Option<u64>,
+}
+
+// A row without a set stands for a game without sets, and a row without a colour and count
+// for an empty set.
+#[derive(Deserialize)]
+struct CsvCube {
+    game: u64,
+    set: Option<u64>,
+    cube: Option<u64>,
+    color: Option<String>,
+    count: Option<u64>,
+}
+
+impl CsvCube {
+    /// Whether this row belongs to the same game as the `previous` row.
+    fn continues(&self, previous: &CsvCube) -> bool {
+        if self.game != previous.game {
+            return false;
+        }
+        let (Some(set), Some(previous_set)) = (self.set, previous.set) else {
+            return false;
+        };
+        if set != previous_set {
+            return set > previous_set;
+        }
+        let is_cube = |row: &CsvCube| row.color.is_some() || row.count.is_some();
+        return is_cube(self)
+            && is_cube(previous)
+            && match (self.cube, previous.cube) {
+                (Some(cube), Some(previous_cube)) => cube > previous_cube,
+                _ => true,
+            };
+    }
+}
+
+/// Parses every game of an in-memory log in the given format, handling bytes that are not
+/// UTF-8 according to `invalid_utf8`. A leading byte order mark is ignored whatever the format.
+pub fn parse_games_as(input: &[u8], format: InputFormat, invalid_utf8: InvalidUtf8) -> Result<Vec<Game>, LineError<ParseError>> {
+    let games = parse_games_by_line_as(input, format, invalid_utf8)?;
+    return Ok(games.into_iter().map(|(_, game)| game).collect());
+}
+
+/// Like [`parse_games_as`], pairing every game with the line it starts on: its own line in
+/// text logs, the line of its first row in CSV logs and the line of its opening brace in
+/// JSON logs.
+pub fn parse_games_by_line_as(input: &[u8], format: InputFormat, invalid_utf8: InvalidUtf8) -> Result<Vec<(usize, Game)>, LineError<ParseError>> {
+    let input = invalid_utf8.apply(strip_bom(input));
+    return match format {
+        InputFormat::Text => parse_games_by_line(&input),
+        InputFormat::Json => parse_json(&input),
+        InputFormat::Csv => parse_csv(&input),
+    };
+}
+
+fn parse_json(input: &[u8]) -> Result<Vec<(usize, Game)>, LineError<ParseError>> {
+    let invalid = |line: usize, error: serde_json::Error| LineError {
+        line: line + error.line() - 1,
+        error: ParseError::InvalidJson(error.to_string()),
+    };
+    // Each game is kept raw first, so its position in the input gives its line.
+    let raw_games = serde_json::from_slice::<Vec<&RawValue>>(input).map_err(|error| invalid(1, error))?;
+    let mut games = Vec::new();
+    for raw_game in raw_games {
+        let offset = raw_game.get().as_ptr() as usize - input.as_ptr() as usize;
+        let line = input[..offset].iter().filter(|&&byte| byte == b'\n').count() + 1;
+        let game = serde_json::from_str::<JsonGame>(raw_game.get()).map_err(|error| invalid(line, error))?;
+        games.push((line, Game {
+            number: game.id,
+            sets: game.sets.into_iter().map(|set| Set {
+                cubes: [(set.red, Color::RED), (set.green, Color::GREEN), (set.blue, Color::BLUE)]
+                    .into_iter()
+                    .filter_map(|(count, color)| Some(Cube { count: count?, color }))
+                    .collect(),
+            }).collect(),
+        }));
+    }
+    return Ok(games);
+}
+
+fn parse_csv(input: &[u8]) -> Result<Vec<(usize, Game)>, LineError<ParseError>> {
+    let invalid = |error: csv::Error| LineError {
+        line: error.position().map_or(0, |position| position.line() as usize),
+        error: ParseError::InvalidCsv(error.to_string()),
+    };
+    let mut reader = csv::Reader::from_reader(input);
+    let headers = reader.headers().map_err(invalid)?.clone();
+    let mut record = csv::StringRecord::new();
+    let mut games: Vec<(usize, Game)> = Vec::new();
+    let mut previous: Option<CsvCube> = None;
+    while reader.read_record(&mut record).map_err(invalid)? {
+        let line = record.position().map_or(0, |position| position.line() as usize);
+        let cube = record.deserialize::<CsvCube>(Some(&headers)).map_err(|error| LineError {
+            line,
+            error: ParseError::InvalidCsv(error.to_string()),
+        })?;
+
+        let same_set = match &previous {
+            Some(previous) if cube.continues(previous) => previous.set == cube.set,
+            _ => {
+                games.push((line, Game { number: cube.game, sets: Vec::new() }));
+                false
+            }
+        };
+        let (_, game) = games.last_mut().unwrap();
+        if cube.set.is_some() && !same_set {
+            game.sets.push(Set { cubes: Vec::new() });
+        }
+        match (&cube.color, cube.count) {
+            (Some(color), Some(count)) => {
+                let color = parse_color(color.trim()).map_err(|error| LineError { line, error })?;
+                game.sets.last_mut().unwrap().cubes.push(Cube { count, color });
+            }
+            (None, None) => {}
+            _ => {
+                let error = ParseError::InvalidCsv("a cube needs both a color and a count".to_string());
+                return Err(LineError { line, error });
+            }
+        }
+        previous = Some(cube);
+    }
+    return Ok(games);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::{write_csv, write_json};
+    use crate::validate::{validate, Issue, IssueKind};
+    use crate::{parse_games, Bag};
+
+    fn test_games() -> Vec<Game> {
+        return parse_games(include_bytes!("test-input.txt")).unwrap();
+    }
+
+    #[test]
+    fn test_input_format_from_path() {
+        assert_eq!(InputFormat::from_path("games.json"), InputFormat::Json);
+        assert_eq!(InputFormat::from_path("./src/games.csv"), InputFormat::Csv);
+        assert_eq!(InputFormat::from_path("./src/input.txt"), InputFormat::Text);
+        assert_eq!(parse_input_format("csv"), Ok(InputFormat::Csv));
+        assert!(parse_input_format("xml").is_err());
+    }
+
+    #[test]
+    fn test_parse_json() {
+        let input = br#"[{"id": 1, "sets": [{"red": 4, "blue": 3}, {"green": 2}]}, {"id": 2, "sets": []}]"#;
+        assert_eq!(
+            parse_games_as(input, InputFormat::Json, InvalidUtf8::Report),
+            Ok(vec![
+                Game {
+                    number: 1,
+                    sets: vec![
+                        Set { cubes: vec![Cube { count: 4, color: Color::RED }, Cube { count: 3, color: Color::BLUE }] },
+                        Set { cubes: vec![Cube { count: 2, color: Color::GREEN }] },
+                    ],
+                },
+                Game { number: 2, sets: vec![] },
+            ])
+        );
+
+        let error = parse_games_as(b"[\n{\"id\": 1, \"sets\": [{\"purple\": 4}]}\n]", InputFormat::Json, InvalidUtf8::Report).unwrap_err();
+        assert_eq!(error.line, 2);
+        assert!(matches!(error.error, ParseError::InvalidJson(message) if message.contains("purple")));
+    }
+
+    #[test]
+    fn test_parse_csv() {
+        let input = b"game,set,color,count\n1,1,blue,3\n1,1,red,4\n1,2,green,2\n3,1,red,20\n";
+        assert_eq!(
+            parse_games_as(input, InputFormat::Csv, InvalidUtf8::Report),
+            Ok(parse_games(b"Game 1: 3 blue, 4 red; 2 green\nGame 3: 20 red").unwrap())
+        );
+
+        let input = b"game,set,color,count\n1,1,blue,3\n1,2,red,4\n1,1,green,2\n";
+        assert_eq!(
+            parse_games_by_line_as(input, InputFormat::Csv, InvalidUtf8::Report),
+            Ok(vec![
+                (2, parse_games(b"Game 1: 3 blue; 4 red").unwrap().remove(0)),
+                (4, parse_games(b"Game 1: 2 green").unwrap().remove(0)),
+            ])
+        );
+        let games = parse_games_by_line_as(input, InputFormat::Csv, InvalidUtf8::Report).unwrap();
+        assert_eq!(
+            validate(games.iter().map(|(line, game)| (*line, game))),
+            vec![Issue { line: 4, game: 1, kind: IssueKind::DuplicateId { first_line: 2 } }]
+        );
+
+        let error = parse_games_as(b"game,set,color,count\n1,1,blue,3\n1,1,purple,4\n", InputFormat::Csv, InvalidUtf8::Report).unwrap_err();
+        assert_eq!(error, LineError { line: 3, error: ParseError::UnknownColor("purple".to_string()) });
+        let error = parse_games_as(b"game,set,color,count\n1,x,blue,3\n", InputFormat::Csv, InvalidUtf8::Report).unwrap_err();
+        assert_eq!(error.line, 2);
+    }
+
+    #[test]
+    fn test_invalid_utf8() {
+        let csv = b"game,set,color,count,note\n1,1,blue,3,caf\xE9\n1,1,red,4,\n";
+        assert!(matches!(parse_games_as(csv, InputFormat::Csv, InvalidUtf8::Report), Err(LineError { error: ParseError::InvalidCsv(_), .. })));
+        assert_eq!(parse_games_as(csv, InputFormat::Csv, InvalidUtf8::Replace), parse_games(b"Game 1: 3 blue, 4 red"));
+
+        let text = b"Game 1: 3 blue\nGame 2: 4 r\xE9d\n";
+        assert_eq!(parse_games_as(text, InputFormat::Text, InvalidUtf8::Report), Err(LineError { line: 2, error: ParseError::InvalidUtf8 }));
+        assert_eq!(
+            parse_games_as(text, InputFormat::Text, InvalidUtf8::Replace),
+            Err(LineError { line: 2, error: ParseError::UnknownColor("r\u{FFFD}d".to_string()) })
+        );
+    }
+
+    #[test]
+    fn test_exports_read_back() {
+        let mut games = test_games();
+        games.extend(parse_games(b"Game 6: 2 blue, 1 red, 3 blue; ; 1 green\nGame 6: 4 red\nGame 6:\nGame 6: 1 red").unwrap());
+        games.push(Game { number: 7, sets: vec![] });
+        games.push(Game { number: 7, sets: vec![] });
+        let mut csv = Vec::new();
+        write_csv(&games, &Bag::default(), &mut csv).unwrap();
+        assert_eq!(parse_games_as(&csv, InputFormat::Csv, InvalidUtf8::Report), Ok(games.clone()));
+
+        // JSON sums repeated colours and orders the cubes of a set by colour.
+        let mut json = Vec::new();
+        write_json(&games, &Bag::default(), &mut json).unwrap();
+        let expected = games.iter().map(|game| Game {
+            number: game.number,
+            sets: game.sets.iter().map(|set| Set {
+                cubes: [Color::RED, Color::GREEN, Color::BLUE].into_iter()
+                    .filter_map(|color| {
+                        let same = set.cubes.iter().filter(|cube| cube.color == color).collect::<Vec<&Cube>>();
+                        match same.is_empty() {
+                            true => None,
+                            false => Some(Cube { count: same.iter().map(|cube| cube.count).sum(), color }),
+                        }
+                    })
+                    .collect(),
+            }).collect(),
+        }).collect::<Vec<Game>>();
+        assert_eq!(parse_games_as(&json, InputFormat::Json, InvalidUtf8::Report), Ok(expected));
+    }
+}
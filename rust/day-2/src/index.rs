@@ -0,0 +1,287 @@
+//! Answers many bag queries over the same game log without re-checking every game.
+//!
+//! A game is possible in a bag exactly when its minimum set fits in the bag, so each game
+//! is reduced to a point (max red, max green, max blue). Every colour axis is compressed to
+//! the distinct maxima that occur, and a three dimensional prefix sum over the compressed
+//! grid holds, per cell, the id sum of every game dominated by that cell. A query then
+//! needs three binary searches and one lookup. The grid has one cell per combination of
+//! distinct maxima, which stays small for puzzle logs where counts rarely exceed twenty.
+//! Logs with so many distinct maxima that the grid would pass [`MAX_CELLS`], as simulated
+//! or imported logs can have, are held in a k-d tree of the points instead. It has one node
+//! per game, and a query only descends into the subtrees whose bounding box straddles the
+//! bag, which is O(n^(2/3)) nodes in three dimensions.
+
+use crate::Color::{BLUE, GREEN, RED};
+use crate::{determine_minimum_cubes, parse_set, Bag, Game, MinimumSet, ParseError};
+
+/// The most cells a grid may have, about 16 MiB of sums and counts.
+pub const MAX_CELLS: usize = 1 << 20;
+
+pub struct BagIndex {
+    lookup: Lookup,
+}
+
+enum Lookup {
+    Grid(Grid),
+    Tree(KdTree),
+}
+
+struct Grid {
+    red: Vec<u64>,
+    green: Vec<u64>,
+    blue: Vec<u64>,
+    id_sums: Vec<u64>,
+    counts: Vec<usize>,
+}
+
+impl BagIndex {
+    pub fn new(games: &[Game]) -> BagIndex {
+        let minimum_sets = games.iter().map(determine_minimum_cubes).collect::<Vec<_>>();
+        let axis = |count: fn(&MinimumSet) -> u64| {
+            let mut values = minimum_sets.iter().map(count).collect::<Vec<u64>>();
+            values.sort_unstable();
+            values.dedup();
+            values
+        };
+        let mut index = Grid {
+            red: axis(|set| set.red.count),
+            green: axis(|set| set.green.count),
+            blue: axis(|set| set.blue.count),
+            id_sums: Vec::new(),
+            counts: Vec::new(),
+        };
+        let cells = (index.red.len() + 1)
+            .checked_mul(index.green.len() + 1)
+            .and_then(|cells| cells.checked_mul(index.blue.len() + 1))
+            .filter(|&cells| cells <= MAX_CELLS);
+        let Some(cells) = cells else {
+            let points = games.iter().zip(&minimum_sets)
+                .map(|(game, set)| ([set.red.count, set.green.count, set.blue.count], game.number))
+                .collect();
+            return BagIndex { lookup: Lookup::Tree(KdTree::new(points)) };
+        };
+        index.id_sums = vec![0; cells];
+        index.counts = vec![0; cells];
+
+        for (game, minimum_set) in games.iter().zip(&minimum_sets) {
+            let cell = index.cell(
+                index.red.binary_search(&minimum_set.red.count).unwrap() + 1,
+                index.green.binary_search(&minimum_set.green.count).unwrap() + 1,
+                index.blue.binary_search(&minimum_set.blue.count).unwrap() + 1,
+            );
+            index.id_sums[cell] += game.number;
+            index.counts[cell] += 1;
+        }
+
+        // Accumulating along one axis at a time turns the point counts into prefix sums.
+        let strides = [index.cell(1, 0, 0), index.cell(0, 1, 0), index.cell(0, 0, 1)];
+        let lengths = [index.red.len() + 1, index.green.len() + 1, index.blue.len() + 1];
+        for (stride, length) in strides.into_iter().zip(lengths) {
+            for cell in 0..cells {
+                if (cell / stride) % length != 0 {
+                    index.id_sums[cell] += index.id_sums[cell - stride];
+                    index.counts[cell] += index.counts[cell - stride];
+                }
+            }
+        }
+        return BagIndex { lookup: Lookup::Grid(index) };
+    }
+
+    /// The sum of the ids of the games that are possible in `bag`.
+    pub fn id_sum(&self, bag: &Bag) -> u64 {
+        return match &self.lookup {
+            Lookup::Grid(grid) => grid.id_sums[grid.query_cell(bag)],
+            Lookup::Tree(tree) => tree.query(&[bag.red, bag.green, bag.blue], 0, tree.points.len()).0,
+        };
+    }
+
+    /// The number of games that are possible in `bag`.
+    pub fn possible_games(&self, bag: &Bag) -> usize {
+        return match &self.lookup {
+            Lookup::Grid(grid) => grid.counts[grid.query_cell(bag)],
+            Lookup::Tree(tree) => tree.query(&[bag.red, bag.green, bag.blue], 0, tree.points.len()).1,
+        };
+    }
+}
+
+impl Grid {
+    fn query_cell(&self, bag: &Bag) -> usize {
+        return self.cell(
+            self.red.partition_point(|&count| count <= bag.red),
+            self.green.partition_point(|&count| count <= bag.green),
+            self.blue.partition_point(|&count| count <= bag.blue),
+        );
+    }
+
+    fn cell(&self, red: usize, green: usize, blue: usize) -> usize {
+        return (red * (self.green.len() + 1) + green) * (self.blue.len() + 1) + blue;
+    }
+}
+
+// The games of a range of `points` form a subtree: the middle game is its root and splits
+// the rest along one colour, cycling red, green, blue with depth. The other vectors hold,
+// at the middle of every range, the bounding box, id sum and count of the whole range.
+struct KdTree {
+    /// The minimum set and id of every game.
+    points: Vec<([u64; 3], u64)>,
+    lower: Vec<[u64; 3]>,
+    upper: Vec<[u64; 3]>,
+    id_sums: Vec<u64>,
+    counts: Vec<usize>,
+}
+
+impl KdTree {
+    fn new(points: Vec<([u64; 3], u64)>) -> KdTree {
+        let length = points.len();
+        let mut tree = KdTree {
+            points,
+            lower: vec![[0; 3]; length],
+            upper: vec![[0; 3]; length],
+            id_sums: vec![0; length],
+            counts: vec![0; length],
+        };
+        tree.build(0, length, 0);
+        return tree;
+    }
+
+    fn build(&mut self, start: usize, end: usize, axis: usize) {
+        if start >= end {
+            return;
+        }
+        let middle = (start + end) / 2;
+        self.points[start..end].select_nth_unstable_by_key(middle - start, |(point, _)| point[axis]);
+        self.build(start, middle, (axis + 1) % 3);
+        self.build(middle + 1, end, (axis + 1) % 3);
+
+        let (point, id) = self.points[middle];
+        let (mut lower, mut upper, mut id_sum, mut count) = (point, point, id, 1);
+        for child in [(start, middle), (middle + 1, end)] {
+            if child.0 < child.1 {
+                let root = (child.0 + child.1) / 2;
+                for axis in 0..3 {
+                    lower[axis] = lower[axis].min(self.lower[root][axis]);
+                    upper[axis] = upper[axis].max(self.upper[root][axis]);
+                }
+                id_sum += self.id_sums[root];
+                count += self.counts[root];
+            }
+        }
+        (self.lower[middle], self.upper[middle]) = (lower, upper);
+        (self.id_sums[middle], self.counts[middle]) = (id_sum, count);
+    }
+
+    // The id sum and count of the games in `start..end` whose point fits in `bag`.
+    fn query(&self, bag: &[u64; 3], start: usize, end: usize) -> (u64, usize) {
+        if start >= end {
+            return (0, 0);
+        }
+        let middle = (start + end) / 2;
+        if fits(&self.upper[middle], bag) {
+            return (self.id_sums[middle], self.counts[middle]);
+        }
+        if !fits(&self.lower[middle], bag) {
+            return (0, 0);
+        }
+        let (point, id) = self.points[middle];
+        let (left_sum, left_count) = self.query(bag, start, middle);
+        let (right_sum, right_count) = self.query(bag, middle + 1, end);
+        let (id_sum, count) = if fits(&point, bag) { (id, 1) } else { (0, 0) };
+        return (left_sum + right_sum + id_sum, left_count + right_count + count);
+    }
+}
+
+fn fits(point: &[u64; 3], bag: &[u64; 3]) -> bool {
+    return point[0] <= bag[0] && point[1] <= bag[1] && point[2] <= bag[2];
+}
+
+/// Parses a bag written like a set, such as `12 red, 13 green, 14 blue`.
+/// Colours that are left out have no cubes in the bag, and a colour listed twice has the
+/// sum of its counts, which must fit in a `u64`.
+pub fn parse_bag(line: &str) -> Result<Bag, ParseError> {
+    let mut bag = Bag { red: 0, green: 0, blue: 0 };
+    for cube in parse_set(line.to_string())?.cubes {
+        let count = match cube.color {
+            RED => &mut bag.red,
+            GREEN => &mut bag.green,
+            BLUE => &mut bag.blue,
+        };
+        *count = count.checked_add(cube.count).ok_or_else(|| ParseError::InvalidCount(cube.to_string()))?;
+    }
+    return Ok(bag);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{is_game_possible_in, parse_games, Cube, Set};
+
+    #[test]
+    fn test_index_matches_scan() {
+        let games = parse_games(include_bytes!("input.txt")).unwrap();
+        let points = games.iter()
+            .map(|game| (determine_minimum_cubes(game), game.number))
+            .map(|(set, id)| ([set.red.count, set.green.count, set.blue.count], id))
+            .collect();
+        let indexes = [BagIndex::new(&games), BagIndex { lookup: Lookup::Tree(KdTree::new(points)) }];
+        for red in 0..22 {
+            for green in (0..22).step_by(3) {
+                for blue in (0..22).step_by(2) {
+                    let bag = Bag { red, green, blue };
+                    let possible = games.iter().filter(|game| is_game_possible_in(game, &bag)).collect::<Vec<&Game>>();
+                    for index in &indexes {
+                        assert_eq!(index.id_sum(&bag), possible.iter().map(|game| game.number).sum::<u64>(), "{}", bag);
+                        assert_eq!(index.possible_games(&bag), possible.len(), "{}", bag);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_index_on_test_input() {
+        let index = BagIndex::new(&parse_games(include_bytes!("test-input.txt")).unwrap());
+        assert_eq!(index.id_sum(&Bag::default()), 8);
+        assert_eq!(index.id_sum(&Bag { red: 100, green: 100, blue: 100 }), 15);
+        assert_eq!(index.id_sum(&Bag { red: 0, green: 0, blue: 0 }), 0);
+        assert_eq!(BagIndex::new(&[]).id_sum(&Bag::default()), 0);
+    }
+
+    #[test]
+    fn test_index_uses_a_tree_for_logs_too_large_for_a_grid() {
+        // 200 distinct counts per colour would need about eight million cells.
+        let games = (1..=200)
+            .map(|number| Game {
+                number,
+                sets: vec![Set {
+                    cubes: vec![
+                        Cube { count: number, color: RED },
+                        Cube { count: 201 - number, color: GREEN },
+                        Cube { count: number * 7 % 200, color: BLUE },
+                    ],
+                }],
+            })
+            .collect::<Vec<Game>>();
+        let index = BagIndex::new(&games);
+        assert!(matches!(index.lookup, Lookup::Tree(_)));
+        let bags = (0..=210).step_by(15)
+            .flat_map(|red| (0..=210).step_by(30).flat_map(move |green| (0..=210).step_by(35).map(move |blue| Bag { red, green, blue })));
+        for bag in bags {
+            let possible = games.iter().filter(|game| is_game_possible_in(game, &bag)).collect::<Vec<&Game>>();
+            assert_eq!(index.id_sum(&bag), possible.iter().map(|game| game.number).sum::<u64>(), "{}", bag);
+            assert_eq!(index.possible_games(&bag), possible.len(), "{}", bag);
+        }
+        assert!(matches!(BagIndex::new(&games[..50]).lookup, Lookup::Grid(_)));
+    }
+
+    #[test]
+    fn test_parse_bag() {
+        assert_eq!(parse_bag("14 blue, 12 red, 13 green"), Ok(Bag::default()));
+        assert_eq!(parse_bag(" 3 red"), Ok(Bag { red: 3, green: 0, blue: 0 }));
+        assert_eq!(parse_bag("3 purple"), Err(ParseError::UnknownColor("purple".to_string())));
+        assert_eq!(parse_bag("3 red, 4 red"), Ok(Bag { red: 7, green: 0, blue: 0 }));
+        assert_eq!(
+            parse_bag("18446744073709551615 blue, 1 blue"),
+            Err(ParseError::InvalidCount("1 blue".to_string()))
+        );
+    }
+}
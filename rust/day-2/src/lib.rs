@@ -0,0 +1,1014 @@
+//! Cube Conundrum, day 2 of Advent of Code 2023.
+//!
+//! A game log lists one game per line, `Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red`,
+//! where every `;` separated set is a handful of cubes revealed from a bag. Games are
+//! possible when no set shows more cubes than the bag holds, and every game has a minimum
+//! set of cubes that makes it possible.
+//!
+//! ```no_run
+//! use day_2::{determine_minimum_cubes, calc_set_power, read_games};
+//!
+//! for game in read_games("./src/input.txt")? {
+//!     match game {
+//!         Ok(game) => println!("Game {}: power {}", game.number, calc_set_power(&determine_minimum_cubes(&game))),
+//!         Err(error) => eprintln!("{}", error),
+//!     }
+//! }
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+pub mod constraints;
+pub mod estimate;
+pub mod export;
+pub mod filter;
+pub mod follow;
+pub mod import;
+pub mod frontier;
+pub mod index;
+pub mod simulate;
+pub mod stats;
+pub mod timing;
+pub mod validate;
+
+use aoc::input_file::{InputFile, LineCursor, LineError};
+use aoc::normalize::{content_lines, InvalidUtf8};
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::path::Path;
+use std::str;
+use crate::import::{parse_games_by_line_as, InputFormat};
+use crate::validate::{apply_policy, Policy};
+use crate::Color::{BLUE, GREEN, RED};
+use crate::timing::{
+    timed, GLOBAL_DETERMINE_MINIMUM_SET, GLOBAL_FIND_POSSIBLE_GAMES, GLOBAL_ID_SUM,
+    GLOBAL_LOAD_FILE_TIMES, GLOBAL_PARSE_GAMES, GLOBAL_POWER_SUM, GLOBAL_SET_POWERS,
+};
+
+/// Why a line of a game log could not be parsed.
+#[derive(PartialEq, Debug, Clone)]
+pub enum ParseError {
+    InvalidUtf8,
+    /// The line has no `:` between the game id and its sets.
+    MissingSeparator(String),
+    InvalidGameNumber(String),
+    /// A cube is not a count followed by a colour.
+    InvalidCube(String),
+    InvalidCount(String),
+    UnknownColor(String),
+    /// A JSON log does not have the expected shape.
+    InvalidJson(String),
+    /// A CSV log row does not have the expected columns.
+    InvalidCsv(String),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self {
+            ParseError::InvalidUtf8 => write!(f, "line is not valid UTF-8"),
+            ParseError::MissingSeparator(line) => write!(f, "missing ':' in {:?}", line),
+            ParseError::InvalidGameNumber(number) => write!(f, "invalid game number {:?}", number),
+            ParseError::InvalidCube(cube) => write!(f, "invalid cube {:?}", cube),
+            ParseError::InvalidCount(count) => write!(f, "invalid cube count {:?}", count),
+            ParseError::UnknownColor(color) => write!(f, "unknown color {:?}", color),
+            ParseError::InvalidJson(message) => write!(f, "invalid JSON: {}", message),
+            ParseError::InvalidCsv(message) => write!(f, "invalid CSV: {}", message),
+        };
+    }
+}
+
+impl Error for ParseError {}
+
+/// Lazily parses the games of a file, one line at a time.
+pub struct Games {
+    cursor: LineCursor,
+}
+
+impl Iterator for Games {
+    type Item = Result<Game, LineError<ParseError>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (line_number, line) = self.cursor.next_content_line()?;
+        return Some(parse_game_bytes(line).map_err(|error| LineError { line: line_number, error }));
+    }
+}
+
+/// Memory-maps the game log at `path` and returns a reader over its games.
+pub fn read_games<P: AsRef<Path>>(path: P) -> io::Result<Games> {
+    return Ok(Games { cursor: InputFile::map(path)?.into_cursor() });
+}
+
+/// Parses every game of an in-memory game log, skipping blank lines and a byte order mark.
+pub fn parse_games(input: &[u8]) -> Result<Vec<Game>, LineError<ParseError>> {
+    return Ok(parse_games_by_line(input)?.into_iter().map(|(_, game)| game).collect());
+}
+
+/// Like [`parse_games`], pairing every game with the number of its line.
+pub fn parse_games_by_line(input: &[u8]) -> Result<Vec<(usize, Game)>, LineError<ParseError>> {
+    return content_lines(input)
+        .map(|(line, bytes)| match parse_game_bytes(bytes) {
+            Ok(game) => Ok((line, game)),
+            Err(error) => Err(LineError { line, error }),
+        })
+        .collect();
+}
+
+/// Answers both parts for the game log at `filename`, recording the time of each stage.
+/// Issues found by [`validate::validate`] are printed as warnings.
+///
+/// # Panics
+///
+/// Panics if the file cannot be read or contains a line that is not a game.
+pub fn process_input(filename: String) -> Summary {
+    return process_input_with(filename, InputFormat::Text, Rules::Independent, Policy::Warn, InvalidUtf8::Report);
+}
+
+/// Like [`process_input`], reading the log in `format`, judging the games under `rules`,
+/// handling issues of the log according to `policy` and bytes that are not UTF-8 according
+/// to `invalid_utf8`.
+///
+/// # Panics
+///
+/// Panics if the file cannot be read, contains a line that is not a game, or is rejected
+/// by `policy`.
+pub fn process_input_with(filename: String, format: InputFormat, rules: Rules, policy: Policy, invalid_utf8: InvalidUtf8) -> Summary {
+    let input = timed(
+        &GLOBAL_LOAD_FILE_TIMES,
+        || InputFile::map(filename).unwrap(),
+    );
+    let games = timed(
+        &GLOBAL_PARSE_GAMES,
+        || parse_games_by_line_as(input.as_bytes(), format, invalid_utf8).unwrap(),
+    );
+    let games = match apply_policy(games, policy) {
+        Ok((games, issues)) => {
+            for issue in issues {
+                eprintln!("{}", issue);
+            }
+            games
+        }
+        Err(issues) => {
+            let issues = issues.iter().map(|issue| issue.to_string()).collect::<Vec<String>>();
+            panic!("rejected the game log:\n{}", issues.join("\n"));
+        }
+    };
+
+    return summarize_timed(&games, &Bag::default(), rules);
+}
+
+/// Answers both parts for already parsed games.
+pub fn summarize(games: &[Game]) -> Summary {
+    return summarize_in(games, &Bag::default());
+}
+
+/// Answers both parts for already parsed games, judging possible games against `bag`.
+pub fn summarize_in(games: &[Game], bag: &Bag) -> Summary {
+    return summarize_with(games, bag, Rules::Independent);
+}
+
+/// Answers both parts for already parsed games, judging possible games against `bag`
+/// and computing minimum sets under `rules`. Unlike [`process_input`], no stage times are
+/// recorded, so it can be called any number of times.
+pub fn summarize_with(games: &[Game], bag: &Bag, rules: Rules) -> Summary {
+    let id_sum = games.iter()
+        .filter(|game| is_game_possible_with(game, bag, rules))
+        .map(|game| game.number)
+        .sum();
+    let power_sum = games.iter()
+        .map(|game| calc_set_power(&determine_minimum_cubes_with(game, rules)))
+        .sum();
+    return Summary{
+        id_sum,
+        power_sum
+    };
+}
+
+// Like summarize_with, recording the time of each stage.
+fn summarize_timed(games: &[Game], bag: &Bag, rules: Rules) -> Summary {
+    let possible_games = timed(
+        &GLOBAL_FIND_POSSIBLE_GAMES,
+        || games
+            .iter()
+            .clone()
+            .filter(|game| is_game_possible_with(game, bag, rules))
+    );
+    let id_sum = timed(
+        &GLOBAL_ID_SUM,
+        || possible_games
+            .fold(
+                0,
+                |acc, game| acc + game.number
+            )
+    );
+
+    let minimum_sets = timed(
+        &GLOBAL_DETERMINE_MINIMUM_SET,
+        || games
+            .iter()
+            .clone()
+            .map(|game| determine_minimum_cubes_with(game, rules))
+    );
+    let set_powers = timed(
+        &GLOBAL_SET_POWERS,
+        || minimum_sets
+            .map(|minimum_set| calc_set_power(&minimum_set))
+    );
+    let power_sum = timed(
+        &GLOBAL_POWER_SUM,
+        || set_powers.sum::<u64>()
+    );
+
+    return Summary{
+        id_sum,
+        power_sum
+    };
+}
+
+/// Whether every set of the game could have been drawn from the bag of 12 red, 13 green and 14 blue cubes.
+pub fn is_game_possible(game: &Game) -> bool {
+    return is_game_possible_in(game, &Bag::default());
+}
+
+/// Whether every set of the game could have been drawn from `bag`.
+pub fn is_game_possible_in(game: &Game, bag: &Bag) -> bool {
+    let impossible_sets = game.sets.iter()
+        .filter(|&set| is_set_impossible_in(set, bag))
+        .collect::<Vec<&Set>>();
+    return impossible_sets.is_empty();
+}
+
+/// Whether the game could have been played with `bag` under `rules`.
+pub fn is_game_possible_with(game: &Game, bag: &Bag, rules: Rules) -> bool {
+    return match rules {
+        Rules::Independent => is_game_possible_in(game, bag),
+        Rules::Sequential => {
+            let drawn = determine_minimum_cubes_with(game, Rules::Sequential);
+            drawn.red.count <= bag.red && drawn.green.count <= bag.green && drawn.blue.count <= bag.blue
+        }
+    };
+}
+
+/// Whether the set shows more cubes of a colour than the bag of 12 red, 13 green and 14 blue cubes holds.
+pub fn is_set_impossible(set: &Set) -> bool {
+    return is_set_impossible_in(set, &Bag::default());
+}
+
+/// Whether the set shows more cubes of a colour than `bag` holds.
+pub fn is_set_impossible_in(set: &Set, bag: &Bag) -> bool {
+    let impossible_cubes = set.cubes.iter()
+        .filter(|&cube| cube.count > bag.count(&cube.color))
+        .collect::<Vec<&Cube>>();
+    return !impossible_cubes.is_empty();
+}
+
+/// The fewest cubes of each colour the bag must have held for the game to be possible.
+pub fn determine_minimum_cubes(game: &Game) -> MinimumSet {
+    let max_red_cube = game.sets.iter()
+        .flat_map(|set| set.cubes.clone())
+        .filter(|cube| cube.color == RED)
+        .max_by_key(|cube| cube.count)
+        .map(|cube| cube.count)
+        .or(Some(0));
+
+    let max_green_cube = game.sets.iter()
+        .flat_map(|set| set.cubes.clone())
+        .filter(|cube| cube.color == GREEN)
+        .max_by_key(|cube| cube.count)
+        .map(|cube| cube.count)
+        .or(Some(0));
+
+    let max_blue_cube = game.sets.iter()
+        .flat_map(|set| set.cubes.clone())
+        .filter(|cube| cube.color == BLUE)
+        .max_by_key(|cube| cube.count)
+        .map(|cube| cube.count)
+        .or(Some(0));
+
+    return MinimumSet{
+        red: Cube { count: max_red_cube.unwrap(), color: RED },
+        green: Cube { count: max_green_cube.unwrap(), color: GREEN },
+        blue: Cube { count: max_blue_cube.unwrap(), color: BLUE },
+    }
+}
+
+/// The fewest cubes of each colour the bag must have held for the game to be possible under
+/// `rules`. When sets are drawn sequentially, the bag must hold every cube drawn in the game.
+pub fn determine_minimum_cubes_with(game: &Game, rules: Rules) -> MinimumSet {
+    if rules == Rules::Independent {
+        return determine_minimum_cubes(game);
+    }
+    let drawn = |color: Color| game.sets.iter()
+        .flat_map(|set| &set.cubes)
+        .filter(|cube| cube.color == color)
+        .map(|cube| cube.count)
+        .sum::<u64>();
+    return MinimumSet {
+        red: Cube { count: drawn(RED), color: RED },
+        green: Cube { count: drawn(GREEN), color: GREEN },
+        blue: Cube { count: drawn(BLUE), color: BLUE },
+    };
+}
+
+/// The power of a set: its red, green and blue counts multiplied together.
+pub fn calc_set_power(minimum_set: &MinimumSet) -> u64 {
+    return minimum_set.red.count * minimum_set.green.count * minimum_set.blue.count
+}
+
+/// Parses a line such as `Game 1: 3 blue, 4 red; 1 red, 2 green`.
+///
+/// Words may be separated by any run of whitespace, and `Game` and the colours may be
+/// written in any case, so hand-edited lines such as `game  3:\t8 Green` are accepted.
+/// A set with no cubes, as in `Game 1: 3 red;`, is read as an empty set for
+/// [`validate::validate`] to report.
+pub fn parse_game(line: &str) -> Result<Game, ParseError> {
+    let splits = line.split(":").collect::<Vec<&str>>();
+    if splits.len() != 2 {
+        return Err(ParseError::MissingSeparator(line.to_string()));
+    }
+
+    let words = splits[0].split_whitespace().collect::<Vec<&str>>();
+    let number_string = match words.as_slice() {
+        [prefix, number] if prefix.eq_ignore_ascii_case("game") => number.to_string(),
+        _ => splits[0].trim().to_string(),
+    };
+    return Ok(Game {
+        number: number_string.parse::<u64>().map_err(|_| ParseError::InvalidGameNumber(number_string.clone()))?,
+        sets: splits[1].split(";")
+            .map(|set_string| {
+                if set_string.trim().is_empty() {
+                    return Ok(Set { cubes: Vec::new() });
+                }
+                return parse_set(set_string.to_string());
+            })
+            .collect::<Result<Vec<Set>, ParseError>>()?,
+    });
+}
+
+pub(crate) fn parse_set(set_string: String) -> Result<Set, ParseError> {
+    return Ok(Set {
+        cubes: set_string
+            .split(",")
+            .map(|cube_string| parse_cub(cube_string.to_string()))
+            .collect::<Result<Vec<Cube>, ParseError>>()?
+    });
+}
+
+fn parse_cub(cube_string: String) -> Result<Cube, ParseError> {
+    let splits = cube_string.split_whitespace().map(|split| split.to_string()).collect::<Vec<String>>();
+    if splits.len() != 2 {
+        return Err(ParseError::InvalidCube(cube_string.trim().to_string()));
+    }
+
+    return Ok(Cube {
+        count: splits[0].parse::<u64>().map_err(|_| ParseError::InvalidCount(splits[0].clone()))?,
+        color: parse_color(&splits[1])?,
+    });
+}
+
+/// Parses a colour name, ignoring its case.
+pub(crate) fn parse_color(name: &str) -> Result<Color, ParseError> {
+    return match name.to_ascii_lowercase().as_str() {
+        "red" => Ok(Color::RED),
+        "green" => Ok(Color::GREEN),
+        "blue" => Ok(Color::BLUE),
+        _ => Err(ParseError::UnknownColor(name.to_string())),
+    };
+}
+
+pub(crate) fn parse_game_bytes(line: &[u8]) -> Result<Game, ParseError> {
+    return parse_game(str::from_utf8(line).map_err(|_| ParseError::InvalidUtf8)?);
+}
+
+/// A game: its id and the sets of cubes revealed from the bag.
+#[derive(PartialEq, Debug, Clone)]
+pub struct Game {
+    pub number: u64,
+    pub sets: Vec<Set>,
+}
+
+/// One handful of cubes revealed from the bag.
+#[derive(PartialEq, Debug, Clone)]
+pub struct Set {
+    pub cubes: Vec<Cube>,
+}
+
+/// The fewest cubes per colour that make a game possible.
+#[derive(PartialEq, Debug, Clone)]
+pub struct MinimumSet {
+    pub red : Cube,
+    pub green : Cube,
+    pub blue : Cube
+}
+
+/// A number of cubes of one colour.
+#[derive(PartialEq, Debug, Clone)]
+pub struct Cube {
+    pub count: u64,
+    pub color: Color,
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub enum Color {
+    BLUE,
+    GREEN,
+    RED,
+}
+
+/// How the sets of a game are drawn from the bag.
+#[derive(PartialEq, Debug, Clone, Copy, Default)]
+pub enum Rules {
+    /// Every set is put back before the next is drawn, as in the puzzle, so each set is
+    /// judged on its own.
+    #[default]
+    Independent,
+    /// Sets are consecutive draws without putting cubes back, so the cubes drawn so far in a
+    /// game add up.
+    Sequential,
+}
+
+/// Parses a rule mode: `independent` or `sequential`.
+pub fn parse_rules(text: &str) -> Result<Rules, String> {
+    return match text {
+        "independent" => Ok(Rules::Independent),
+        "sequential" => Ok(Rules::Sequential),
+        _ => Err(format!("unknown rules {:?}, expected independent or sequential", text)),
+    };
+}
+
+/// The number of cubes of each colour in the bag. Defaults to the bag from the puzzle.
+#[derive(PartialEq, Debug, Clone, Copy, serde::Serialize)]
+pub struct Bag {
+    pub red: u64,
+    pub green: u64,
+    pub blue: u64,
+}
+
+impl Bag {
+    /// The number of cubes in the bag.
+    pub fn total(&self) -> u64 {
+        return self.red + self.green + self.blue;
+    }
+
+    pub fn count(&self, color: &Color) -> u64 {
+        return match color {
+            RED => self.red,
+            GREEN => self.green,
+            BLUE => self.blue,
+        };
+    }
+}
+
+impl Default for Bag {
+    fn default() -> Bag {
+        return Bag {
+            red: 12,
+            green: 13,
+            blue: 14,
+        };
+    }
+}
+
+/// The puzzle answers: the id sum of the possible games and the power sum of the minimum sets.
+#[derive(PartialEq, Debug, Clone)]
+pub struct Summary {
+    pub id_sum : u64,
+    pub power_sum : u64
+}
+
+impl fmt::Display for Game {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Game {}:", self.number)?;
+        for (index, set) in self.sets.iter().enumerate() {
+            write!(f, "{} {}", if index == 0 { "" } else { ";" }, set)?;
+        }
+        return Ok(());
+    }
+}
+
+impl fmt::Display for Set {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, cube) in self.cubes.iter().enumerate() {
+            write!(f, "{}{}", if index == 0 { "" } else { ", " }, cube)?;
+        }
+        return Ok(());
+    }
+}
+
+impl fmt::Display for Cube {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return write!(f, "{} {}", self.count, self.color);
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return write!(f, "{}", match self {
+            RED => "red",
+            GREEN => "green",
+            BLUE => "blue",
+        });
+    }
+}
+
+impl fmt::Display for MinimumSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return write!(f, "{}, {}, {}", self.red, self.green, self.blue);
+    }
+}
+
+impl fmt::Display for Bag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return write!(f, "{} red, {} green, {} blue", self.red, self.green, self.blue);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timing::{report_global_times, reset_global_times, GLOBAL_PROCESS};
+
+    #[test]
+    fn test_process_input() {
+        reset_global_times();
+        let actual = timed(
+            &GLOBAL_PROCESS,
+            || process_input("./src/test-input.txt".to_string()),
+        );
+        report_global_times("Test Process Input".to_string());
+        assert_eq!(
+            actual,
+            Summary {
+                id_sum : 8,
+                power_sum : 2286
+            }
+        )
+    }
+
+    #[test]
+    fn test_process_input_formats() {
+        let path = std::env::temp_dir().join(format!("day-2-process-{}.csv", std::process::id()));
+        let mut csv = Vec::new();
+        export::write_csv(&parse_games(include_bytes!("test-input.txt")).unwrap(), &Bag::default(), &mut csv).unwrap();
+        std::fs::write(&path, csv).unwrap();
+        let filename = path.to_str().unwrap().to_string();
+        assert_eq!(
+            process_input_with(filename, InputFormat::Csv, Rules::Independent, Policy::Warn, InvalidUtf8::Report),
+            Summary { id_sum: 8, power_sum: 2286 }
+        );
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_sequential_rules() {
+        let game = parse_game("Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red").unwrap();
+        assert_eq!(
+            determine_minimum_cubes_with(&game, Rules::Sequential),
+            MinimumSet {
+                red: Cube { count: 23, color: RED },
+                green: Cube { count: 7, color: GREEN },
+                blue: Cube { count: 21, color: BLUE },
+            }
+        );
+        assert_eq!(determine_minimum_cubes_with(&game, Rules::Independent), determine_minimum_cubes(&game));
+        assert!(is_game_possible_with(&game, &Bag { red: 23, green: 7, blue: 21 }, Rules::Sequential));
+        assert!(!is_game_possible_with(&game, &Bag { red: 22, green: 20, blue: 30 }, Rules::Sequential));
+        assert!(is_game_possible_with(&game, &Bag { red: 14, green: 3, blue: 15 }, Rules::Independent));
+
+        let games = parse_games(include_bytes!("test-input.txt")).unwrap();
+        assert_eq!(summarize_with(&games, &Bag::default(), Rules::Sequential), Summary { id_sum: 8, power_sum: 180 + 36 + 7150 + 3381 + 105 });
+        assert_eq!(parse_rules("sequential"), Ok(Rules::Sequential));
+        assert!(parse_rules("random").is_err());
+    }
+
+    #[test]
+    fn test_parse_game() {
+        let actual = parse_game("Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red");
+        assert_eq!(
+            actual,
+            Ok(Game {
+                number: 3,
+                sets: vec![
+                    Set {
+                        cubes: vec![
+                            Cube { count: 8, color: GREEN },
+                            Cube { count: 6, color: BLUE },
+                            Cube { count: 20, color: RED },
+                        ]
+                    },
+                    Set {
+                        cubes: vec![
+                            Cube { count: 5, color: BLUE },
+                            Cube { count: 4, color: RED },
+                            Cube { count: 13, color: GREEN },
+                        ]
+                    },
+                    Set {
+                        cubes: vec![
+                            Cube { count: 5, color: GREEN },
+                            Cube { count: 1, color: RED },
+                        ]
+                    },
+                ],
+            })
+        )
+    }
+
+    #[test]
+    fn test_parse_game_errors() {
+        assert_eq!(parse_game("Game 3 8 green"), Err(ParseError::MissingSeparator("Game 3 8 green".to_string())));
+        assert_eq!(parse_game("Game x: 8 green"), Err(ParseError::InvalidGameNumber("x".to_string())));
+        assert_eq!(parse_game("Game 3: 8green"), Err(ParseError::InvalidCube("8green".to_string())));
+        assert_eq!(parse_game("Game 3: eight green"), Err(ParseError::InvalidCount("eight".to_string())));
+        assert_eq!(parse_game("Game 3: 8 purple"), Err(ParseError::UnknownColor("purple".to_string())));
+    }
+
+    #[test]
+    fn test_parse_game_tolerates_spacing_and_case() {
+        let expected = parse_game("Game 3: 8 green, 6 blue; 1 red").unwrap();
+        assert_eq!(parse_game("Game  3:\t8 Green,6   BLUE ;1 red  ").unwrap(), expected);
+        assert_eq!(parse_game(" game 3 : 8 green, 6 blue; 1 Red").unwrap(), expected);
+        assert_eq!(parse_game("Game 3: 8 Purple"), Err(ParseError::UnknownColor("Purple".to_string())));
+    }
+
+    #[test]
+    fn test_parse_games_normalizes_input() {
+        let plain = parse_games(b"Game 1: 3 blue, 4 red\nGame 2: 1 green\n").unwrap();
+        assert_eq!(parse_games(b"\xEF\xBB\xBFGame 1: 3 blue, 4 red\r\nGame 2: 1 green\r\n\r\n\n").unwrap(), plain);
+        assert_eq!(
+            parse_games(b"Game 1: 3 blue\n\nGame x: 1 green"),
+            Err(LineError { line: 3, error: ParseError::InvalidGameNumber("x".to_string()) })
+        );
+    }
+
+    #[test]
+    fn test_read_games() {
+        let games = read_games("./src/test-input.txt").unwrap().collect::<Vec<_>>();
+        assert_eq!(games.len(), 5);
+        assert_eq!(games[4].as_ref().unwrap().number, 5);
+    }
+
+    #[test]
+    fn test_parse_games_reports_line() {
+        let actual = parse_games(b"Game 1: 3 blue\nGame 2: 3 purple\n");
+        assert_eq!(
+            actual,
+            Err(LineError { line: 2, error: ParseError::UnknownColor("purple".to_string()) })
+        )
+    }
+
+    #[test]
+    fn test_is_red_game_possible_true () {
+        let actual = is_game_possible(
+            &Game {
+                number: 1,
+                sets : vec![
+                    Set {
+                        cubes: vec![
+                            Cube { count: 12, color: RED },
+                        ]
+                    }
+                ]
+            }
+        );
+        assert_eq!(
+            actual,
+            true
+        )
+    }
+
+    #[test]
+    fn test_is_red_game_possible_false () {
+        let actual = is_game_possible(
+            &Game {
+                number: 1,
+                sets : vec![
+                    Set {
+                        cubes: vec![
+                            Cube { count: 13, color: RED },
+                        ]
+                    }
+                ]
+            }
+        );
+        assert_eq!(
+            actual,
+            false
+        )
+    }
+
+    #[test]
+    fn test_is_green_game_possible_true () {
+        let actual = is_game_possible(
+            &Game {
+                number: 1,
+                sets : vec![
+                    Set {
+                        cubes: vec![
+                            Cube { count: 13, color: GREEN },
+                        ]
+                    }
+                ]
+            }
+        );
+        assert_eq!(
+            actual,
+            true
+        )
+    }
+
+    #[test]
+    fn test_is_green_game_possible_false () {
+        let actual = is_game_possible(
+            &Game {
+                number: 1,
+                sets : vec![
+                    Set {
+                        cubes: vec![
+                            Cube { count: 14, color: GREEN },
+                        ]
+                    }
+                ]
+            }
+        );
+        assert_eq!(
+            actual,
+            false
+        )
+    }
+
+
+    #[test]
+    fn test_is_blue_game_possible_true () {
+        let actual = is_game_possible(
+            &Game {
+                number: 1,
+                sets : vec![
+                    Set {
+                        cubes: vec![
+                            Cube { count: 14, color: BLUE },
+                        ]
+                    }
+                ]
+            }
+        );
+        assert_eq!(
+            actual,
+            true
+        )
+    }
+
+    #[test]
+    fn test_is_blue_game_possible_false () {
+        let actual = is_game_possible(
+            &Game {
+                number: 1,
+                sets : vec![
+                    Set {
+                        cubes: vec![
+                            Cube { count: 15, color: BLUE },
+                        ]
+                    }
+                ]
+            }
+        );
+        assert_eq!(
+            actual,
+            false
+        )
+    }
+
+    #[test]
+    fn test_is_game_possible_in_smaller_bag () {
+        let game = parse_game("Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green").unwrap();
+        assert_eq!(is_game_possible_in(&game, &Bag { red: 4, green: 2, blue: 6 }), true);
+        assert_eq!(is_game_possible_in(&game, &Bag { red: 3, green: 2, blue: 6 }), false);
+    }
+
+    #[test]
+    fn test_display_round_trips () {
+        let line = "Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red";
+        assert_eq!(parse_game(line).unwrap().to_string(), line);
+    }
+
+    #[test]
+    fn test_determine_minimum_cubes_just_red () {
+        let actual = determine_minimum_cubes(
+            &Game {
+                number : 1,
+                sets : vec![
+                    Set {
+                        cubes: vec![
+                            Cube { count: 10, color: RED },
+                        ]
+                    }
+                ]
+            }
+        );
+        assert_eq!(
+            actual,
+            MinimumSet {
+                red : Cube { count : 10, color : RED},
+                green : Cube { count : 0, color : GREEN},
+                blue : Cube { count : 0, color : BLUE}
+            }
+        )
+    }
+
+    #[test]
+    fn test_determine_minimum_cubes_just_green () {
+        let actual = determine_minimum_cubes(
+            &Game {
+                number : 1,
+                sets : vec![
+                    Set {
+                        cubes: vec![
+                            Cube { count: 15, color: GREEN },
+                        ]
+                    }
+                ]
+            }
+        );
+        assert_eq!(
+            actual,
+            MinimumSet {
+                red : Cube { count : 0, color : RED},
+                green : Cube { count : 15, color : GREEN},
+                blue : Cube { count : 0, color : BLUE}
+            }
+        )
+    }
+
+    #[test]
+    fn test_determine_minimum_cubes_just_blue () {
+        let actual = determine_minimum_cubes(
+            &Game {
+                number : 1,
+                sets : vec![
+                    Set {
+                        cubes: vec![
+                            Cube { count: 20, color: BLUE },
+                        ]
+                    }
+                ]
+            }
+        );
+        assert_eq!(
+            actual,
+            MinimumSet {
+                red : Cube { count : 0, color : RED},
+                green : Cube { count : 0, color : GREEN},
+                blue : Cube { count : 20, color : BLUE}
+            }
+        )
+    }
+
+    #[test]
+    fn test_determine_minimum_cubes_game_one () {
+        let actual = determine_minimum_cubes(
+            &parse_game("Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green").unwrap()
+        );
+        assert_eq!(
+            actual,
+            MinimumSet {
+                red : Cube { count : 4, color : RED},
+                green : Cube { count : 2, color : GREEN},
+                blue : Cube { count : 6, color : BLUE}
+            }
+        )
+    }
+
+    #[test]
+    fn test_determine_minimum_cubes_game_two () {
+        let actual = determine_minimum_cubes(
+            &parse_game("Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue").unwrap()
+        );
+        assert_eq!(
+            actual,
+            MinimumSet {
+                red : Cube { count : 1, color : RED},
+                green : Cube { count : 3, color : GREEN},
+                blue : Cube { count : 4, color : BLUE}
+            }
+        )
+    }
+
+    #[test]
+    fn test_determine_minimum_cubes_game_three () {
+        let actual = determine_minimum_cubes(
+            &parse_game("Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red").unwrap()
+        );
+        assert_eq!(
+            actual,
+            MinimumSet {
+                red : Cube { count : 20, color : RED},
+                green : Cube { count : 13, color : GREEN},
+                blue : Cube { count : 6, color : BLUE}
+            }
+        )
+    }
+
+    #[test]
+    fn test_determine_minimum_cubes_game_four () {
+        let actual = determine_minimum_cubes(
+            &parse_game("Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red").unwrap()
+        );
+        assert_eq!(
+            actual,
+            MinimumSet {
+                red : Cube { count : 14, color : RED},
+                green : Cube { count : 3, color : GREEN},
+                blue : Cube { count : 15, color : BLUE}
+            }
+        )
+    }
+
+    #[test]
+    fn test_determine_minimum_cubes_game_five () {
+        let actual = determine_minimum_cubes(
+            &parse_game("Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green").unwrap()
+        );
+        assert_eq!(
+            actual,
+            MinimumSet {
+                red : Cube { count : 6, color : RED},
+                green : Cube { count : 3, color : GREEN},
+                blue : Cube { count : 2, color : BLUE}
+            }
+        )
+    }
+
+    #[test]
+    fn test_calc_set_power_game_one() {
+        let actual = calc_set_power(
+            &MinimumSet {
+                red : Cube { count : 4, color : RED},
+                green : Cube { count : 2, color : GREEN},
+                blue : Cube { count : 6, color : BLUE}
+            }
+        );
+        assert_eq!(
+            actual,
+            48
+        )
+    }
+
+    #[test]
+    fn test_calc_set_power_game_two() {
+        let actual = calc_set_power(
+            &MinimumSet {
+                red : Cube { count : 1, color : RED},
+                green : Cube { count : 3, color : GREEN},
+                blue : Cube { count : 4, color : BLUE}
+            }
+        );
+        assert_eq!(
+            actual,
+            12
+        )
+    }
+
+    #[test]
+    fn test_calc_set_power_game_three() {
+        let actual = calc_set_power(
+            &MinimumSet {
+                red : Cube { count : 20, color : RED},
+                green : Cube { count : 13, color : GREEN},
+                blue : Cube { count : 6, color : BLUE}
+            }
+        );
+        assert_eq!(
+            actual,
+            1560
+        )
+    }
+
+    #[test]
+    fn test_calc_set_power_game_four() {
+        let actual = calc_set_power(
+            &MinimumSet {
+                red : Cube { count : 14, color : RED},
+                green : Cube { count : 3, color : GREEN},
+                blue : Cube { count : 15, color : BLUE}
+            }
+        );
+        assert_eq!(
+            actual,
+            630
+        )
+    }
+
+    #[test]
+    fn test_calc_set_power_game_five() {
+        let actual = calc_set_power(
+            &MinimumSet {
+                red : Cube { count : 6, color : RED},
+                green : Cube { count : 3, color : GREEN},
+                blue : Cube { count : 2, color : BLUE}
+            }
+        );
+        assert_eq!(
+            actual,
+            36
+        )
+    }
+}
\ No newline at end of file
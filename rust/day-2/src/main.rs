@@ -1,738 +1,468 @@
+mod repl;
+mod tui;
+
+use aoc::input_file::InputFile;
+use aoc::normalize::{parse_invalid_utf8, InvalidUtf8};
+use clap::error::ErrorKind;
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
+use day_2::constraints::{load_constraints, violations};
+use day_2::estimate::{estimate_bag, parse_prior, Prior, Priors, MAX_COUNT};
+use day_2::export::{write_csv, write_json};
+use day_2::filter::Filter;
+use day_2::follow::Follower;
+use day_2::frontier::{cheapest_bag, pareto_frontier, smallest_change};
+use day_2::import::{parse_games_by_line_as, parse_input_format, InputFormat};
+use day_2::index::{parse_bag, BagIndex};
+use day_2::simulate::{parse_range, simulate, Replacement, Simulation};
+use day_2::stats::describe;
+use day_2::timing::{report_global_times, reset_global_times, timed, GLOBAL_PROCESS};
+use day_2::validate::{apply_policy, parse_policy, validate, Policy, Severity};
+use day_2::{parse_rules, process_input_with, summarize_with, Bag, Game, Rules};
 use std::fs::File;
-use std::io::{self, BufRead, BufReader, Lines};
+use std::io::{self, BufWriter, Write};
+use std::ops::RangeInclusive;
 use std::path::Path;
-use std::time::Instant;
-use std::cell::RefCell;
-use std::thread::LocalKey;
-use crate::Color::{BLUE, GREEN, RED};
-
-thread_local!(
-    static GLOBAL_PROCESS: RefCell<Vec<u64>> = RefCell::new(Vec::new());
-    static GLOBAL_LOAD_FILE_TIMES: RefCell<Vec<u64>> = RefCell::new(Vec::new());
-    static GLOBAL_PARSE_GAMES: RefCell<Vec<u64>> = RefCell::new(Vec::new());
-    static GLOBAL_FIND_POSSIBLE_GAMES: RefCell<Vec<u64>> = RefCell::new(Vec::new());
-    static GLOBAL_ID_SUM: RefCell<Vec<u64>> = RefCell::new(Vec::new());
-    static GLOBAL_DETERMINE_MINIMUM_SET: RefCell<Vec<u64>> = RefCell::new(Vec::new());
-    static GLOBAL_SET_POWERS: RefCell<Vec<u64>> = RefCell::new(Vec::new());
-    static GLOBAL_POWER_SUM: RefCell<Vec<u64>> = RefCell::new(Vec::new());
-);
-
-fn main() {
-    reset_global_times();
-    let result = timed(
-        &GLOBAL_PROCESS,
-        || process_input("./src/input.txt".to_string()),
-    );
-    report_global_times("Process Input".to_string());
-    println!("Sum of possible games: {}", result.id_sum);
-    println!("Power Sum of games: {}", result.power_sum);
+use std::process;
+use std::thread;
+use std::time::Duration;
+
+const INPUT: &str = "./src/input.txt";
+
+#[derive(Parser)]
+#[command(name = "day-2", about = "Cube Conundrum: solves and explores game logs")]
+struct Cli {
+    /// Only solves the games matching an expression like `max(red) > 10 and any_set(blue >= 5)`
+    #[arg(long = "where", value_name = "EXPRESSION")]
+    filter: Option<String>,
+    /// Format of the game logs that are read, `text`, `json` or `csv`; defaults to the file extension
+    #[arg(long, global = true, value_parser = parse_input_format)]
+    input_format: Option<InputFormat>,
+    /// What to do with bytes of the game logs that are not UTF-8: `report` them or `replace` them with U+FFFD
+    #[arg(long, global = true, default_value = "report", value_parser = parse_invalid_utf8)]
+    invalid_utf8: InvalidUtf8,
+    /// How the sets of a game are drawn, `independent` as in the puzzle (the default) or `sequential`
+    #[arg(long, value_parser = parse_rules)]
+    rules: Option<Rules>,
+    /// What to do with issues of the game log: `warn` (the default), `merge` duplicates or `reject` it
+    #[arg(long, value_parser = parse_policy)]
+    policy: Option<Policy>,
+    #[command(subcommand)]
+    command: Option<Command>,
 }
 
-fn process_input(filename: String) -> Result {
-    let lines = timed(
-        &GLOBAL_LOAD_FILE_TIMES,
-        || read_lines(filename).unwrap(),
-    );
-    let games = timed(
-        &GLOBAL_PARSE_GAMES,
-        || lines.map(|line| parse_game(line.unwrap())).collect::<Vec<Game>>(),
-    );
-
-    let possible_games = timed(
-        &GLOBAL_FIND_POSSIBLE_GAMES,
-        || games
-            .iter()
-            .clone()
-            .filter(|game| is_game_possible(game))
-    );
-    let id_sum = timed(
-        &GLOBAL_ID_SUM,
-        || possible_games
-            .fold(
-                0,
-                |acc, game| acc + game.number
-            )
-    );
-
-    let minimum_sets = timed(
-        &GLOBAL_DETERMINE_MINIMUM_SET,
-        || games
-            .iter()
-            .clone()
-            .map(|game| determine_minimum_cubes(game))
-    );
-    let set_powers = timed(
-        &GLOBAL_SET_POWERS,
-        || minimum_sets
-            .map(|minimum_set| calc_set_power(&minimum_set))
-    );
-    let power_sum = timed(
-        &GLOBAL_POWER_SUM,
-        || set_powers
-            .fold(
-                0,
-                |acc, set_power| acc + set_power
-            )
-    );
-
-    return Result{
-        id_sum,
-        power_sum
-    };
+#[derive(Subcommand)]
+enum Command {
+    /// Explores a game log interactively
+    Repl {
+        #[arg(default_value = INPUT)]
+        input: String,
+    },
+    /// Follows a game log as it grows and keeps the answers up to date
+    Follow {
+        #[arg(default_value = INPUT)]
+        input: String,
+        /// Milliseconds between checks for new lines
+        #[arg(long, default_value_t = 500)]
+        interval: u64,
+    },
+    /// Answers a file of bags, one per line like `12 red, 13 green, 14 blue`, with the id sum of the games possible in each
+    Query {
+        queries: String,
+        #[arg(long, default_value = INPUT)]
+        input: String,
+    },
+    /// Answers questions about the bags that make games possible
+    Bags {
+        #[arg(long, default_value = INPUT)]
+        input: String,
+        #[command(subcommand)]
+        question: BagQuestion,
+    },
+    /// Estimates the most likely bag contents from the drawn sets
+    Estimate {
+        #[arg(long, default_value = INPUT)]
+        input: String,
+        /// Only use the sets of this game
+        #[arg(long)]
+        game: Option<u64>,
+        /// Largest number of cubes of a colour that is considered, at most 200
+        #[arg(long, default_value_t = 30, value_parser = clap::value_parser!(u64).range(..=MAX_COUNT))]
+        max: u64,
+        /// Prior of the red count, `uniform` or `poisson:<mean>`
+        #[arg(long, default_value = "uniform", value_parser = parse_prior)]
+        red_prior: Prior,
+        #[arg(long, default_value = "uniform", value_parser = parse_prior)]
+        green_prior: Prior,
+        #[arg(long, default_value = "uniform", value_parser = parse_prior)]
+        blue_prior: Prior,
+    },
+    /// Generates a seeded game log from a known bag
+    Simulate {
+        #[arg(long, default_value = "12 red, 13 green, 14 blue")]
+        bag: String,
+        #[arg(long, default_value_t = 100)]
+        games: usize,
+        /// Sets per game, like `1-6`
+        #[arg(long, default_value = "1-6", value_parser = parse_range)]
+        sets: RangeInclusive<u64>,
+        /// Cubes per set, like `1-15`
+        #[arg(long, default_value = "1-15", value_parser = parse_range)]
+        handful: RangeInclusive<u64>,
+        /// Keeps drawn cubes out of the bag until the game ends
+        #[arg(long)]
+        without_replacement: bool,
+        #[arg(long, default_value_t = 1)]
+        seed: u64,
+    },
+    /// Writes the games with their minimum sets, powers and possible flags as CSV or JSON
+    Export {
+        output: String,
+        #[arg(long, default_value = INPUT)]
+        input: String,
+        /// Defaults to the extension of the output
+        #[arg(long, value_enum)]
+        format: Option<ExportFormat>,
+        #[arg(long, default_value = "12 red, 13 green, 14 blue")]
+        bag: String,
+    },
+    /// Describes the distributions of cube counts, sets per game and powers
+    Stats {
+        #[arg(default_value = INPUT)]
+        input: String,
+        /// Prints the statistics as JSON instead of a table
+        #[arg(long)]
+        json: bool,
+    },
+    /// Reports the sets that violate the constraints of a rules file
+    Check {
+        rules: String,
+        #[arg(long, default_value = INPUT)]
+        input: String,
+    },
+    /// Checks a game log for duplicate colours and ids, ids out of sequence, empty sets and zero counts
+    Validate {
+        #[arg(default_value = INPUT)]
+        input: String,
+    },
+    /// Shows a game log in a full-screen dashboard with adjustable bag limits
+    Tui {
+        #[arg(default_value = INPUT)]
+        input: String,
+    },
 }
 
-fn is_game_possible(game: &Game) -> bool {
-    let impossible_sets = game.sets.iter()
-        .filter(|&set| is_set_impossible(set))
-        .collect::<Vec<&Set>>();
-    return impossible_sets.is_empty();
+#[derive(Clone, Copy, ValueEnum)]
+enum ExportFormat {
+    Csv,
+    Json,
 }
 
-fn is_set_impossible(set: &Set) -> bool {
-    let impossible_cubes = set.cubes.iter()
-        .filter(|&cube| {
-            return if cube.color == RED  { cube.count > 12 }
-            else if cube.color == GREEN  { cube.count > 13 }
-            else if cube.color == BLUE  { cube.count > 14 }
-            else { panic!("Unknown color") }
-        })
-        .collect::<Vec<&Cube>>();
-    return !impossible_cubes.is_empty();
+#[derive(Subcommand)]
+enum BagQuestion {
+    /// Lists the minimal bags that make at least K games possible
+    Frontier { k: usize },
+    /// Finds the bag with the fewest cubes that makes the given games possible
+    Cheapest {
+        #[arg(required = true)]
+        ids: Vec<u64>,
+    },
+    /// Finds the fewest cubes to add to a bag so one more game becomes possible
+    Grow {
+        #[arg(default_value = "12 red, 13 green, 14 blue")]
+        bag: String,
+    },
 }
 
-fn determine_minimum_cubes(game: &Game) -> MinimumSet {
-    let max_red_cube = game.sets.iter()
-        .flat_map(|set| set.cubes.clone())
-        .filter(|cube| cube.color == RED)
-        .max_by_key(|cube| cube.count)
-        .map(|cube| cube.count)
-        .or_else( || Some(0));
-
-    let max_green_cube = game.sets.iter()
-        .flat_map(|set| set.cubes.clone())
-        .filter(|cube| cube.color == GREEN)
-        .max_by_key(|cube| cube.count)
-        .map(|cube| cube.count)
-        .or_else( || Some(0));
-
-    let max_blue_cube = game.sets.iter()
-        .flat_map(|set| set.cubes.clone())
-        .filter(|cube| cube.color == BLUE)
-        .max_by_key(|cube| cube.count)
-        .map(|cube| cube.count)
-        .or_else( || Some(0));
-
-    return MinimumSet{
-        red: Cube { count: max_red_cube.unwrap(), color: RED },
-        green: Cube { count: max_green_cube.unwrap(), color: GREEN },
-        blue: Cube { count: max_blue_cube.unwrap(), color: BLUE },
+fn main() {
+    let cli = Cli::parse();
+    // These only shape the default solve, so a subcommand would silently ignore them.
+    if cli.command.is_some() && (cli.filter.is_some() || cli.rules.is_some() || cli.policy.is_some()) {
+        Cli::command()
+            .error(ErrorKind::ArgumentConflict, "--where, --rules and --policy cannot be used with a subcommand")
+            .exit();
     }
-}
-
-fn calc_set_power(minimum_set: &MinimumSet) -> u64 {
-    return minimum_set.red.count * minimum_set.green.count * minimum_set.blue.count
-}
-
-fn parse_game(line: String) -> Game {
-    let splits = line.split(":").collect::<Vec<&str>>();
-
-    return Game {
-        number: splits[0].to_string().replace("Game ", "").parse::<u64>().unwrap(),
-        sets: splits[1].split(";")
-            .map(|set_string| set_string.to_string())
-            .map(|set_string| parse_set(set_string))
-            .collect(),
-    };
-}
-
-fn parse_set(set_string: String) -> Set {
-    return Set {
-        cubes: set_string
-            .split(",")
-            .map(|cube_string| parse_cub(cube_string.to_string()))
-            .collect::<Vec<Cube>>()
-    };
-}
-
-fn parse_cub(cube_string: String) -> Cube {
-    let trimmed_cube_string = cube_string.trim();
-    let splits = trimmed_cube_string.split(" ").map(|split| split.to_string()).collect::<Vec<String>>();
-
-    return Cube {
-        count: splits[0].parse::<u64>().unwrap(),
-        color: if splits[1] == "red" { Color::RED } else if splits[1] == "green" { Color::GREEN } else if splits[1] == "blue" { Color::BLUE } else { panic!("Unknown color") },
-    };
-}
-
-#[derive(PartialEq, Debug)]
-struct Game {
-    number: u64,
-    sets: Vec<Set>,
-}
-
-#[derive(PartialEq, Debug, Clone)]
-struct Set {
-    cubes: Vec<Cube>,
-}
-
-#[derive(PartialEq, Debug)]
-struct MinimumSet {
-    red : Cube,
-    green : Cube,
-    blue : Cube
-}
-
-#[derive(PartialEq, Debug, Clone)]
-struct Cube {
-    count: u64,
-    color: Color,
-}
-
-#[derive(PartialEq, Debug, Clone)]
-enum Color {
-    BLUE,
-    GREEN,
-    RED,
-}
-
-#[derive(PartialEq, Debug, Clone)]
-struct Result {
-    id_sum : u64,
-    power_sum : u64
-}
-
-fn timed<R, F: FnOnce() -> R>(ref_cell_key: &'static LocalKey<RefCell<Vec<u64>>>, func: F) -> R {
-    let stopwatch = Instant::now();
-    let result = func();
-    ref_cell_key.with(|times| {
-        let mut borrowed_times = times.borrow_mut();
-        borrowed_times.push(stopwatch.elapsed().as_nanos() as u64)
-    });
-    return result;
-}
-
-// The output is wrapped in a Result to allow matching on errors
-// Returns an Iterator to the Reader of the lines of the file.
-fn read_lines<P>(filename: P) -> io::Result<Lines<BufReader<File>>>
-    where
-        P: AsRef<Path>,
-{
-    let file = File::open(filename)?;
-    return Ok(BufReader::new(file).lines());
-}
-
-fn reset_global_times() {
-    GLOBAL_PROCESS.with(|times| {
-        times.borrow_mut().clear()
-    });
-    GLOBAL_LOAD_FILE_TIMES.with(|times| {
-        times.borrow_mut().clear()
-    });
-    GLOBAL_PARSE_GAMES.with(|times| {
-        times.borrow_mut().clear()
-    });
-    GLOBAL_FIND_POSSIBLE_GAMES.with(|times| {
-        times.borrow_mut().clear()
-    });
-    GLOBAL_ID_SUM.with(|times| {
-        times.borrow_mut().clear()
-    });
-    GLOBAL_DETERMINE_MINIMUM_SET.with(|times| {
-        times.borrow_mut().clear()
-    });
-    GLOBAL_SET_POWERS.with(|times| {
-        times.borrow_mut().clear()
-    });
-    GLOBAL_POWER_SUM.with(|times| {
-        times.borrow_mut().clear()
-    });
-}
-
-fn report_global_times(prefix: String) {
-    println!(
-        "{} - Overall: Executions {}, Time {:.2}ns",
-        prefix,
-        GLOBAL_PROCESS.with(|times| {
-            times.borrow().len() as f64
-        }),
-        GLOBAL_PROCESS.with(|times| {
-            let borrowed_times = times.borrow();
-            borrowed_times.iter().sum::<u64>() as f64 / borrowed_times.len() as f64
-        })
-    );
-    println!(
-        "{} - Load File: Executions {}, Time {:.2}ns",
-        prefix,
-        GLOBAL_LOAD_FILE_TIMES.with(|times| {
-            times.borrow().len() as f64
-        }),
-        GLOBAL_LOAD_FILE_TIMES.with(|times| {
-            let borrowed_times = times.borrow();
-            borrowed_times.iter().sum::<u64>() as f64 / borrowed_times.len() as f64
-        })
-    );
-    println!(
-        "{} - Parse Game: Executions {}, Time {:.2}ns",
-        prefix,
-        GLOBAL_PARSE_GAMES.with(|times| {
-            times.borrow().len() as f64
-        }),
-        GLOBAL_PARSE_GAMES.with(|times| {
-            let borrowed_times = times.borrow();
-            borrowed_times.iter().sum::<u64>() as f64 / borrowed_times.len() as f64
-        })
-    );
-    println!(
-        "{} - Find Possible Games: Executions {}, Time {:.2}ns",
-        prefix,
-        GLOBAL_FIND_POSSIBLE_GAMES.with(|times| {
-            times.borrow().len() as f64
-        }),
-        GLOBAL_FIND_POSSIBLE_GAMES.with(|times| {
-            let borrowed_times = times.borrow();
-            borrowed_times.iter().sum::<u64>() as f64 / borrowed_times.len() as f64
-        })
-    );
-    println!(
-        "{} - Sum ID: Executions {}, Time {:.2}ns",
-        prefix,
-        GLOBAL_ID_SUM.with(|times| {
-            times.borrow().len() as f64
-        }),
-        GLOBAL_ID_SUM.with(|times| {
-            let borrowed_times = times.borrow();
-            borrowed_times.iter().sum::<u64>() as f64 / borrowed_times.len() as f64
-        })
-    );
-    println!(
-        "{} - Determine Minimum Set: Executions {}, Time {:.2}ns",
-        prefix,
-        GLOBAL_DETERMINE_MINIMUM_SET.with(|times| {
-            times.borrow().len() as f64
-        }),
-        GLOBAL_DETERMINE_MINIMUM_SET.with(|times| {
-            let borrowed_times = times.borrow();
-            borrowed_times.iter().sum::<u64>() as f64 / borrowed_times.len() as f64
-        })
-    );
-    println!(
-        "{} - Calculate Set Powers: Executions {}, Time {:.2}ns",
-        prefix,
-        GLOBAL_SET_POWERS.with(|times| {
-            times.borrow().len() as f64
-        }),
-        GLOBAL_SET_POWERS.with(|times| {
-            let borrowed_times = times.borrow();
-            borrowed_times.iter().sum::<u64>() as f64 / borrowed_times.len() as f64
-        })
-    );
-    println!(
-        "{} - Calculate Power Sum: Executions {}, Time {:.2}ns",
-        prefix,
-        GLOBAL_POWER_SUM.with(|times| {
-            times.borrow().len() as f64
-        }),
-        GLOBAL_POWER_SUM.with(|times| {
-            let borrowed_times = times.borrow();
-            borrowed_times.iter().sum::<u64>() as f64 / borrowed_times.len() as f64
-        })
-    );
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_process_input() {
-        reset_global_times();
-        let actual = timed(
-            &GLOBAL_PROCESS,
-            || process_input("./src/test-input.txt".to_string()),
-        );
-        report_global_times("Test Process Input".to_string());
-        assert_eq!(
-            actual,
-            Result {
-                id_sum : 8,
-                power_sum : 2286
+    let (rules, policy) = (cli.rules.unwrap_or_default(), cli.policy.unwrap_or(Policy::Warn));
+    let reading = Reading { format: cli.input_format, invalid_utf8: cli.invalid_utf8 };
+    match cli.command {
+        None => match cli.filter {
+            Some(filter) => solve_where(&filter, reading, rules, policy),
+            None => solve(reading, rules, policy),
+        },
+        Some(Command::Repl { input }) => repl::run(&input, reading),
+        Some(Command::Tui { input }) => tui::run(&input, reading),
+        Some(Command::Bags { input, question }) => bags(&input, reading, question),
+        Some(Command::Estimate { input, game, max, red_prior, green_prior, blue_prior }) => {
+            let priors = Priors { red: red_prior, green: green_prior, blue: blue_prior, max };
+            estimate(&input, reading, game, &priors)
+        }
+        Some(Command::Simulate { bag, games, sets, handful, without_replacement, seed }) => {
+            let bag = parse_bag(&bag).unwrap_or_else(|error| {
+                eprintln!("Invalid bag: {}", error);
+                process::exit(1);
+            });
+            if bag.total() == 0 {
+                eprintln!("Invalid bag: it holds no cubes to draw");
+                process::exit(1);
             }
-        )
-    }
-
-    #[test]
-    fn test_parse_game() {
-        let actual = parse_game("Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red".to_string());
-        assert_eq!(
-            actual,
-            Game {
-                number: 3,
-                sets: vec![
-                    Set {
-                        cubes: vec![
-                            Cube { count: 8, color: GREEN },
-                            Cube { count: 6, color: BLUE },
-                            Cube { count: 20, color: RED },
-                        ]
-                    },
-                    Set {
-                        cubes: vec![
-                            Cube { count: 5, color: BLUE },
-                            Cube { count: 4, color: RED },
-                            Cube { count: 13, color: GREEN },
-                        ]
-                    },
-                    Set {
-                        cubes: vec![
-                            Cube { count: 5, color: GREEN },
-                            Cube { count: 1, color: RED },
-                        ]
-                    },
-                ],
+            let simulation = Simulation {
+                bag,
+                games,
+                sets,
+                handful,
+                replacement: if without_replacement { Replacement::Without } else { Replacement::With },
+                seed,
+            };
+            for game in simulate(&simulation) {
+                println!("{}", game);
             }
-        )
-    }
-
-    #[test]
-    fn test_is_red_game_possible_true () {
-        let actual = is_game_possible(
-            &Game {
-                number: 1,
-                sets : vec![
-                    Set {
-                        cubes: vec![
-                            Cube { count: 12, color: RED },
-                        ]
-                    }
-                ]
+        }
+        Some(Command::Export { output, input, format, bag }) => export(&output, &input, reading, format, &bag),
+        Some(Command::Stats { input, json }) => {
+            let stats = describe(&load_games(&input, reading));
+            if json {
+                println!("{}", serde_json::to_string_pretty(&stats).unwrap());
+            } else {
+                print!("{}", stats);
             }
-        );
-        assert_eq!(
-            actual,
-            true
-        )
-    }
-
-    #[test]
-    fn test_is_red_game_possible_false () {
-        let actual = is_game_possible(
-            &Game {
-                number: 1,
-                sets : vec![
-                    Set {
-                        cubes: vec![
-                            Cube { count: 13, color: RED },
-                        ]
-                    }
-                ]
+        }
+        Some(Command::Check { rules, input }) => check(&rules, &input, reading),
+        Some(Command::Validate { input }) => {
+            let games = load_games_by_line(&input, reading);
+            let issues = validate(games.iter().map(|(line, game)| (*line, game)));
+            for issue in &issues {
+                println!("{}", issue);
             }
-        );
-        assert_eq!(
-            actual,
-            false
-        )
-    }
-
-    #[test]
-    fn test_is_green_game_possible_true () {
-        let actual = is_game_possible(
-            &Game {
-                number: 1,
-                sets : vec![
-                    Set {
-                        cubes: vec![
-                            Cube { count: 13, color: GREEN },
-                        ]
-                    }
-                ]
+            let errors = issues.iter().filter(|issue| issue.severity() == Severity::Error).count();
+            println!("{} errors, {} warnings", errors, issues.len() - errors);
+            if errors > 0 {
+                process::exit(1);
             }
-        );
-        assert_eq!(
-            actual,
-            true
-        )
+        }
+        Some(Command::Query { queries, input }) => query(&queries, &input, reading),
+        Some(Command::Follow { input, interval }) => follow(&input, Duration::from_millis(interval), cli.invalid_utf8),
     }
+}
 
-    #[test]
-    fn test_is_green_game_possible_false () {
-        let actual = is_game_possible(
-            &Game {
-                number: 1,
-                sets : vec![
-                    Set {
-                        cubes: vec![
-                            Cube { count: 14, color: GREEN },
-                        ]
-                    }
-                ]
-            }
-        );
-        assert_eq!(
-            actual,
-            false
-        )
+fn solve(reading: Reading, rules: Rules, policy: Policy) {
+    let format = reading.format.unwrap_or_else(|| InputFormat::from_path(INPUT));
+    reset_global_times();
+    let result = timed(
+        &GLOBAL_PROCESS,
+        || process_input_with(INPUT.to_string(), format, rules, policy, reading.invalid_utf8),
+    );
+    report_global_times("Process Input".to_string());
+    println!("Sum of possible games: {}", result.id_sum);
+    println!("Power Sum of games: {}", result.power_sum);
+    // The recorded answers only hold for the puzzle's rules.
+    if rules == Rules::Independent {
+        aoc::answers::report("./answers.toml", &[(1, result.id_sum), (2, result.power_sum)]);
     }
+}
 
-
-    #[test]
-    fn test_is_blue_game_possible_true () {
-        let actual = is_game_possible(
-            &Game {
-                number: 1,
-                sets : vec![
-                    Set {
-                        cubes: vec![
-                            Cube { count: 14, color: BLUE },
-                        ]
-                    }
-                ]
+fn solve_where(filter: &str, reading: Reading, rules: Rules, policy: Policy) {
+    let filter = Filter::parse(filter).unwrap_or_else(|error| {
+        eprintln!("Invalid --where expression at {}", error);
+        process::exit(1);
+    });
+    let games = match apply_policy(load_games_by_line(INPUT, reading), policy) {
+        Ok((games, issues)) => {
+            for issue in issues {
+                eprintln!("{}", issue);
             }
-        );
-        assert_eq!(
-            actual,
-            true
-        )
-    }
-
-    #[test]
-    fn test_is_blue_game_possible_false () {
-        let actual = is_game_possible(
-            &Game {
-                number: 1,
-                sets : vec![
-                    Set {
-                        cubes: vec![
-                            Cube { count: 15, color: BLUE },
-                        ]
-                    }
-                ]
+            games
+        }
+        Err(issues) => {
+            for issue in issues {
+                eprintln!("{}", issue);
             }
-        );
-        assert_eq!(
-            actual,
-            false
-        )
-    }
+            eprintln!("Rejected {} because of the errors above", INPUT);
+            process::exit(1);
+        }
+    };
+    let games = games.into_iter()
+        .filter(|game| filter.matches(game))
+        .collect::<Vec<Game>>();
+    let ids = games.iter().map(|game| game.number.to_string()).collect::<Vec<String>>();
+    println!("Matching games ({}): {}", games.len(), ids.join(", "));
+    let result = summarize_with(&games, &Bag::default(), rules);
+    println!("Sum of possible games: {}", result.id_sum);
+    println!("Power Sum of games: {}", result.power_sum);
+}
 
-    #[test]
-    fn test_determine_minimum_cubes_just_red () {
-        let actual = determine_minimum_cubes(
-            &Game {
-                number : 1,
-                sets : vec![
-                    Set {
-                        cubes: vec![
-                            Cube { count: 10, color: RED },
-                        ]
-                    }
-                ]
-            }
-        );
-        assert_eq!(
-            actual,
-            MinimumSet {
-                red : Cube { count : 10, color : RED},
-                green : Cube { count : 0, color : GREEN},
-                blue : Cube { count : 0, color : BLUE}
-            }
-        )
+fn export(output: &str, input: &str, reading: Reading, format: Option<ExportFormat>, bag: &str) {
+    let format = format.or_else(|| match Path::new(output).extension().and_then(|extension| extension.to_str()) {
+        Some("csv") => Some(ExportFormat::Csv),
+        Some("json") => Some(ExportFormat::Json),
+        _ => None,
+    }).unwrap_or_else(|| {
+        eprintln!("Cannot tell the format of {}, pass --format", output);
+        process::exit(1);
+    });
+    let bag = parse_bag(bag).unwrap_or_else(|error| {
+        eprintln!("Invalid bag: {}", error);
+        process::exit(1);
+    });
+    let games = load_games(input, reading);
+    let result = File::create(output).map(BufWriter::new).and_then(|mut writer| {
+        match format {
+            ExportFormat::Csv => write_csv(&games, &bag, &mut writer).map_err(io::Error::other)?,
+            ExportFormat::Json => write_json(&games, &bag, &mut writer).map_err(io::Error::other)?,
+        }
+        return writer.flush();
+    });
+    match result {
+        Ok(()) => println!("Exported {} games to {}", games.len(), output),
+        Err(error) => {
+            eprintln!("Could not write {}: {}", output, error);
+            process::exit(1);
+        }
     }
+}
 
-    #[test]
-    fn test_determine_minimum_cubes_just_green () {
-        let actual = determine_minimum_cubes(
-            &Game {
-                number : 1,
-                sets : vec![
-                    Set {
-                        cubes: vec![
-                            Cube { count: 15, color: GREEN },
-                        ]
-                    }
-                ]
-            }
-        );
-        assert_eq!(
-            actual,
-            MinimumSet {
-                red : Cube { count : 0, color : RED},
-                green : Cube { count : 15, color : GREEN},
-                blue : Cube { count : 0, color : BLUE}
-            }
-        )
+fn check(rules: &str, input: &str, reading: Reading) {
+    let constraints = load_constraints(rules).unwrap_or_else(|error| {
+        eprintln!("Could not load {}: {}", rules, error);
+        process::exit(1);
+    });
+    let games = load_games(input, reading);
+    let mut valid_games = Vec::new();
+    for game in &games {
+        let violations = violations(game, &constraints);
+        if violations.is_empty() {
+            valid_games.push(game.number);
+        }
+        for violation in violations {
+            println!("Game {} set {}: violates {}", game.number, violation.set, violation.constraint);
+        }
     }
+    println!("Games within the constraints: {} of {}", valid_games.len(), games.len());
+    println!("Id sum: {}", valid_games.iter().sum::<u64>());
+}
 
-    #[test]
-    fn test_determine_minimum_cubes_just_blue () {
-        let actual = determine_minimum_cubes(
-            &Game {
-                number : 1,
-                sets : vec![
-                    Set {
-                        cubes: vec![
-                            Cube { count: 20, color: BLUE },
-                        ]
-                    }
-                ]
+fn bags(input: &str, reading: Reading, question: BagQuestion) {
+    let games = load_games(input, reading);
+    match question {
+        BagQuestion::Frontier { k } => {
+            let frontier = pareto_frontier(&games, k);
+            if frontier.is_empty() {
+                println!("The log has fewer than {} games", k);
             }
-        );
-        assert_eq!(
-            actual,
-            MinimumSet {
-                red : Cube { count : 0, color : RED},
-                green : Cube { count : 0, color : GREEN},
-                blue : Cube { count : 20, color : BLUE}
+            for bag in frontier {
+                println!("{} ({} cubes)", bag, bag.total());
             }
-        )
-    }
-
-    #[test]
-    fn test_determine_minimum_cubes_game_one () {
-        let actual = determine_minimum_cubes(
-            &parse_game("Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green".to_string())
-        );
-        assert_eq!(
-            actual,
-            MinimumSet {
-                red : Cube { count : 4, color : RED},
-                green : Cube { count : 2, color : GREEN},
-                blue : Cube { count : 6, color : BLUE}
+        }
+        BagQuestion::Cheapest { ids } => match cheapest_bag(&games, &ids) {
+            Some(bag) => println!("{} ({} cubes)", bag, bag.total()),
+            None => {
+                eprintln!("Not every game of {:?} is in {}", ids, input);
+                process::exit(1);
             }
-        )
-    }
-
-    #[test]
-    fn test_determine_minimum_cubes_game_two () {
-        let actual = determine_minimum_cubes(
-            &parse_game("Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue".to_string())
-        );
-        assert_eq!(
-            actual,
-            MinimumSet {
-                red : Cube { count : 1, color : RED},
-                green : Cube { count : 3, color : GREEN},
-                blue : Cube { count : 4, color : BLUE}
+        },
+        BagQuestion::Grow { bag } => {
+            let bag = parse_bag(&bag).unwrap_or_else(|error| {
+                eprintln!("Invalid bag: {}", error);
+                process::exit(1);
+            });
+            match smallest_change(&games, &bag) {
+                Some(change) => println!("Add {} cubes for game {}: {}", change.added, change.game, change.bag),
+                None => println!("Every game is already possible with {}", bag),
             }
-        )
+        }
     }
+}
 
-    #[test]
-    fn test_determine_minimum_cubes_game_three () {
-        let actual = determine_minimum_cubes(
-            &parse_game("Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red".to_string())
-        );
-        assert_eq!(
-            actual,
-            MinimumSet {
-                red : Cube { count : 20, color : RED},
-                green : Cube { count : 13, color : GREEN},
-                blue : Cube { count : 6, color : BLUE}
-            }
-        )
+fn estimate(input: &str, reading: Reading, game: Option<u64>, priors: &Priors) {
+    let mut games = load_games(input, reading);
+    if let Some(id) = game {
+        games.retain(|game| game.number == id);
+        if games.is_empty() {
+            eprintln!("Game {} is not in {}", id, input);
+            process::exit(1);
+        }
     }
-
-    #[test]
-    fn test_determine_minimum_cubes_game_four () {
-        let actual = determine_minimum_cubes(
-            &parse_game("Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red".to_string())
-        );
-        assert_eq!(
-            actual,
-            MinimumSet {
-                red : Cube { count : 14, color : RED},
-                green : Cube { count : 3, color : GREEN},
-                blue : Cube { count : 15, color : BLUE}
-            }
-        )
+    let posterior = match estimate_bag(&games, priors) {
+        Some(posterior) => posterior,
+        None => {
+            eprintln!("The sets show more than {} cubes of a colour, raise --max", priors.max);
+            process::exit(1);
+        }
+    };
+    println!("Most likely bag: {}", posterior.map);
+    println!("{:<6} {:>6} {:>5} {:>9}", "Colour", "Mean", "Mode", "90%");
+    for color in [&posterior.red, &posterior.green, &posterior.blue] {
+        println!("{:<6} {:>6.2} {:>5} {:>9}", color.color.to_string(), color.mean, color.mode, format!("{}-{}", color.lower, color.upper));
     }
+}
 
-    #[test]
-    fn test_determine_minimum_cubes_game_five () {
-        let actual = determine_minimum_cubes(
-            &parse_game("Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green".to_string())
-        );
-        assert_eq!(
-            actual,
-            MinimumSet {
-                red : Cube { count : 6, color : RED},
-                green : Cube { count : 3, color : GREEN},
-                blue : Cube { count : 2, color : BLUE}
+fn query(queries: &str, input: &str, reading: Reading) {
+    let index = BagIndex::new(&load_games(input, reading));
+    let mut cursor = match InputFile::map(queries) {
+        Ok(file) => file.into_cursor(),
+        Err(error) => {
+            eprintln!("Could not load {}: {}", queries, error);
+            process::exit(1);
+        }
+    };
+    while let Some((line_number, line)) = cursor.next_line() {
+        let line = String::from_utf8_lossy(line);
+        if line.trim().is_empty() {
+            continue;
+        }
+        match parse_bag(&line) {
+            Ok(bag) => println!("{}: {}", bag, index.id_sum(&bag)),
+            Err(error) => {
+                eprintln!("{}: line {}: {}", queries, line_number, error);
+                process::exit(1);
             }
-        )
+        }
     }
+}
 
-    #[test]
-    fn test_calc_set_power_game_one() {
-        let actual = calc_set_power(
-            &MinimumSet {
-                red : Cube { count : 4, color : RED},
-                green : Cube { count : 2, color : GREEN},
-                blue : Cube { count : 6, color : BLUE}
+fn follow(input: &str, interval: Duration, invalid_utf8: InvalidUtf8) {
+    let mut follower = Follower::new(input, Bag::default(), invalid_utf8);
+    loop {
+        let games = match follower.poll() {
+            Ok(games) => games,
+            Err(error) => {
+                eprintln!("Could not read {}: {}", input, error);
+                process::exit(1);
             }
-        );
-        assert_eq!(
-            actual,
-            48
-        )
-    }
-
-    #[test]
-    fn test_calc_set_power_game_two() {
-        let actual = calc_set_power(
-            &MinimumSet {
-                red : Cube { count : 1, color : RED},
-                green : Cube { count : 3, color : GREEN},
-                blue : Cube { count : 4, color : BLUE}
+        };
+        if !games.is_empty() {
+            for error in games.iter().filter_map(|game| game.as_ref().err()) {
+                eprintln!("Skipping {}", error);
             }
-        );
-        assert_eq!(
-            actual,
-            12
-        )
+            let summary = follower.summary();
+            println!("Games: {}, Sum of possible games: {}, Power Sum of games: {}", follower.games(), summary.id_sum, summary.power_sum);
+        }
+        thread::sleep(interval);
     }
+}
 
-    #[test]
-    fn test_calc_set_power_game_three() {
-        let actual = calc_set_power(
-            &MinimumSet {
-                red : Cube { count : 20, color : RED},
-                green : Cube { count : 13, color : GREEN},
-                blue : Cube { count : 6, color : BLUE}
-            }
-        );
-        assert_eq!(
-            actual,
-            1560
-        )
-    }
+/// How the game logs named on the command line are read.
+#[derive(Clone, Copy)]
+struct Reading {
+    format: Option<InputFormat>,
+    invalid_utf8: InvalidUtf8,
+}
 
-    #[test]
-    fn test_calc_set_power_game_four() {
-        let actual = calc_set_power(
-            &MinimumSet {
-                red : Cube { count : 14, color : RED},
-                green : Cube { count : 3, color : GREEN},
-                blue : Cube { count : 15, color : BLUE}
-            }
-        );
-        assert_eq!(
-            actual,
-            630
-        )
-    }
+/// Parses every game of the log at `input`, exiting with a message if that fails.
+/// Without an explicit format, the extension of `input` decides how it is read.
+fn load_games(input: &str, reading: Reading) -> Vec<Game> {
+    return load_games_by_line(input, reading).into_iter().map(|(_, game)| game).collect();
+}
 
-    #[test]
-    fn test_calc_set_power_game_five() {
-        let actual = calc_set_power(
-            &MinimumSet {
-                red : Cube { count : 6, color : RED},
-                green : Cube { count : 3, color : GREEN},
-                blue : Cube { count : 2, color : BLUE}
-            }
-        );
-        assert_eq!(
-            actual,
-            36
-        )
-    }
-}
\ No newline at end of file
+/// Like [`load_games`], pairing every game with the line it starts on.
+fn load_games_by_line(input: &str, reading: Reading) -> Vec<(usize, Game)> {
+    let format = reading.format.unwrap_or_else(|| InputFormat::from_path(input));
+    let games = InputFile::map(input).and_then(|file| {
+        parse_games_by_line_as(file.as_bytes(), format, reading.invalid_utf8).map_err(io::Error::other)
+    });
+    return match games {
+        Ok(games) => games,
+        Err(error) => {
+            eprintln!("Could not load {}: {}", input, error);
+            process::exit(1);
+        }
+    };
+}
@@ -0,0 +1,187 @@
+//! Interactive exploration of a game log.
+
+use crate::{load_games, Reading};
+use day_2::{calc_set_power, determine_minimum_cubes, is_game_possible_in, Bag, Game};
+use rustyline::completion::Completer;
+use rustyline::error::ReadlineError;
+use rustyline::history::DefaultHistory;
+use rustyline::{Context, Editor, Helper, Highlighter, Hinter, Validator};
+use std::env;
+use std::path::PathBuf;
+
+const COMMANDS: [&str; 8] = ["game", "limits", "possible", "min", "power", "help", "quit", "exit"];
+const ID_COMMANDS: [&str; 3] = ["game", "min", "power"];
+const HELP: &str = "\
+game <id>              show a game
+limits [<r> <g> <b>]   show or change the bag used by `possible`
+possible               list the games possible with the current bag and their id sum
+min <id>               show the minimum set of a game
+power <id>             show the power of the minimum set of a game
+quit                   leave the repl";
+
+pub struct Session {
+    games: Vec<Game>,
+    bag: Bag,
+}
+
+impl Session {
+    pub fn new(games: Vec<Game>) -> Session {
+        return Session {
+            games,
+            bag: Bag::default(),
+        };
+    }
+
+    /// Runs one command, returning its output or a message explaining what was wrong with it.
+    pub fn execute(&mut self, line: &str) -> Result<String, String> {
+        let words = line.split_whitespace().collect::<Vec<&str>>();
+        return match words.as_slice() {
+            ["help"] => Ok(HELP.to_string()),
+            ["game", id] => Ok(self.game(id)?.to_string()),
+            ["min", id] => Ok(determine_minimum_cubes(self.game(id)?).to_string()),
+            ["power", id] => Ok(calc_set_power(&determine_minimum_cubes(self.game(id)?)).to_string()),
+            ["limits"] => Ok(self.bag.to_string()),
+            ["limits", red, green, blue] => {
+                self.bag = Bag {
+                    red: parse_count(red)?,
+                    green: parse_count(green)?,
+                    blue: parse_count(blue)?,
+                };
+                Ok(self.bag.to_string())
+            }
+            ["possible"] => {
+                let possible = self.games.iter()
+                    .filter(|game| is_game_possible_in(game, &self.bag))
+                    .collect::<Vec<&Game>>();
+                Ok(format!(
+                    "Possible games ({}): {}\nId sum: {}",
+                    self.bag,
+                    possible.iter().map(|game| game.number.to_string()).collect::<Vec<String>>().join(", "),
+                    possible.iter().map(|game| game.number).sum::<u64>()
+                ))
+            }
+            [] => Ok(String::new()),
+            _ => Err(format!("Unknown command {:?}, try `help`", line.trim())),
+        };
+    }
+
+    fn game(&self, id: &str) -> Result<&Game, String> {
+        let number = parse_count(id)?;
+        return self.games.iter()
+            .find(|game| game.number == number)
+            .ok_or_else(|| format!("No game {}", number));
+    }
+}
+
+fn parse_count(word: &str) -> Result<u64, String> {
+    return word.parse::<u64>().map_err(|_| format!("{:?} is not a number", word));
+}
+
+#[derive(Helper, Hinter, Highlighter, Validator)]
+struct ReplHelper {
+    game_ids: Vec<String>,
+}
+
+impl Completer for ReplHelper {
+    type Candidate = String;
+
+    // Completes command names, and game ids after the commands that take one.
+    fn complete(&self, line: &str, pos: usize, _ctx: &Context<'_>) -> rustyline::Result<(usize, Vec<String>)> {
+        let before_cursor = &line[..pos];
+        let start = before_cursor.rfind(' ').map(|index| index + 1).unwrap_or(0);
+        let prefix = &before_cursor[start..];
+        let command = before_cursor.split_whitespace().next().unwrap_or("");
+
+        let candidates: Vec<String> = if start == 0 {
+            COMMANDS.iter().map(|command| command.to_string()).collect()
+        } else if ID_COMMANDS.contains(&command) {
+            self.game_ids.clone()
+        } else {
+            Vec::new()
+        };
+        return Ok((start, candidates.into_iter().filter(|candidate| candidate.starts_with(prefix)).collect()));
+    }
+}
+
+fn history_path() -> PathBuf {
+    return env::var("HOME").map(PathBuf::from).unwrap_or_default().join(".day-2-history");
+}
+
+pub fn run(input: &str, reading: Reading) {
+    let games = load_games(input, reading);
+    println!("Loaded {} games from {}, type `help` for commands", games.len(), input);
+
+    let mut editor: Editor<ReplHelper, DefaultHistory> = Editor::new().expect("terminal not supported");
+    editor.set_helper(Some(ReplHelper {
+        game_ids: games.iter().map(|game| game.number.to_string()).collect(),
+    }));
+    let _ = editor.load_history(&history_path());
+
+    let mut session = Session::new(games);
+    loop {
+        match editor.readline("day-2> ") {
+            Ok(line) => {
+                let _ = editor.add_history_entry(line.as_str());
+                if matches!(line.trim(), "quit" | "exit") {
+                    break;
+                }
+                match session.execute(&line) {
+                    Ok(output) if output.is_empty() => {}
+                    Ok(output) => println!("{}", output),
+                    Err(message) => println!("{}", message),
+                }
+            }
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(error) => {
+                eprintln!("{}", error);
+                break;
+            }
+        }
+    }
+    let _ = editor.save_history(&history_path());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use day_2::parse_games;
+
+    fn test_session() -> Session {
+        return Session::new(parse_games(include_bytes!("test-input.txt")).unwrap());
+    }
+
+    #[test]
+    fn test_game_min_and_power() {
+        let mut session = test_session();
+        assert_eq!(session.execute("game 2"), Ok("Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue".to_string()));
+        assert_eq!(session.execute("min 1"), Ok("4 red, 2 green, 6 blue".to_string()));
+        assert_eq!(session.execute("power 3"), Ok("1560".to_string()));
+    }
+
+    #[test]
+    fn test_possible_follows_limits() {
+        let mut session = test_session();
+        assert_eq!(session.execute("possible"), Ok("Possible games (12 red, 13 green, 14 blue): 1, 2, 5\nId sum: 8".to_string()));
+        assert_eq!(session.execute("limits 20 13 15"), Ok("20 red, 13 green, 15 blue".to_string()));
+        assert_eq!(session.execute("possible"), Ok("Possible games (20 red, 13 green, 15 blue): 1, 2, 3, 4, 5\nId sum: 15".to_string()));
+    }
+
+    #[test]
+    fn test_invalid_commands() {
+        let mut session = test_session();
+        assert_eq!(session.execute("game 9"), Err("No game 9".to_string()));
+        assert_eq!(session.execute("min x"), Err("\"x\" is not a number".to_string()));
+        assert_eq!(session.execute("limits 1 2"), Err("Unknown command \"limits 1 2\", try `help`".to_string()));
+    }
+
+    #[test]
+    fn test_completion() {
+        let helper = ReplHelper { game_ids: vec!["1".to_string(), "12".to_string(), "2".to_string()] };
+        let history = DefaultHistory::new();
+        let context = Context::new(&history);
+        assert_eq!(helper.complete("po", 2, &context).unwrap(), (0, vec!["possible".to_string(), "power".to_string()]));
+        assert_eq!(helper.complete("game 1", 6, &context).unwrap(), (5, vec!["1".to_string(), "12".to_string()]));
+        assert_eq!(helper.complete("limits 1", 8, &context).unwrap(), (7, vec![]));
+    }
+}
@@ -0,0 +1,213 @@
+//! Generates game logs from a known bag, so the analyses can be checked against ground truth.
+//!
+//! Every set is a handful of cubes drawn one by one without replacement from the cubes that
+//! are in the bag at that moment. With replacement, the handful goes back into the bag before
+//! the next set, as in the puzzle. Without replacement, drawn cubes stay out until the game
+//! ends, and a game stops early once the bag is empty.
+
+use crate::Color::{BLUE, GREEN, RED};
+use crate::{Bag, Cube, Game, Set};
+use std::ops::RangeInclusive;
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum Replacement {
+    With,
+    Without,
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct Simulation {
+    pub bag: Bag,
+    pub games: usize,
+    /// Number of sets per game, drawn uniformly.
+    pub sets: RangeInclusive<u64>,
+    /// Number of cubes per set, drawn uniformly and capped by the cubes left in the bag.
+    pub handful: RangeInclusive<u64>,
+    pub replacement: Replacement,
+    pub seed: u64,
+}
+
+/// A small SplitMix64 generator, so logs are reproducible from their seed on every platform.
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        return Rng { state: seed };
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        return z ^ (z >> 31);
+    }
+
+    /// A uniformly distributed number below `bound`, which must not be zero.
+    pub fn below(&mut self, bound: u64) -> u64 {
+        return ((self.next_u64() as u128 * bound as u128) >> 64) as u64;
+    }
+
+    pub fn in_range(&mut self, range: &RangeInclusive<u64>) -> u64 {
+        return range.start() + self.below(range.end() - range.start() + 1);
+    }
+}
+
+/// Plays the games of `simulation`, numbered from 1.
+pub fn simulate(simulation: &Simulation) -> Vec<Game> {
+    let mut rng = Rng::new(simulation.seed);
+    return (1..=simulation.games as u64)
+        .map(|number| {
+            let mut bag = simulation.bag;
+            let mut game = Game { number, sets: Vec::new() };
+            for _ in 0..rng.in_range(&simulation.sets) {
+                if bag.total() == 0 {
+                    break;
+                }
+                let handful = rng.in_range(&simulation.handful).min(bag.total());
+                let (set, remaining) = draw(&mut rng, bag, handful);
+                game.sets.push(set);
+                if simulation.replacement == Replacement::Without {
+                    bag = remaining;
+                }
+            }
+            game
+        })
+        .collect();
+}
+
+// Draws `handful` cubes from `bag`, returning them as a set in a random colour order
+// together with the cubes left behind.
+fn draw(rng: &mut Rng, mut bag: Bag, handful: u64) -> (Set, Bag) {
+    let mut drawn = Bag { red: 0, green: 0, blue: 0 };
+    for _ in 0..handful {
+        let cube = rng.below(bag.total());
+        if cube < bag.red {
+            bag.red -= 1;
+            drawn.red += 1;
+        } else if cube < bag.red + bag.green {
+            bag.green -= 1;
+            drawn.green += 1;
+        } else {
+            bag.blue -= 1;
+            drawn.blue += 1;
+        }
+    }
+
+    let mut cubes = [RED, GREEN, BLUE]
+        .into_iter()
+        .map(|color| Cube { count: drawn.count(&color), color })
+        .filter(|cube| cube.count > 0)
+        .collect::<Vec<Cube>>();
+    for index in (1..cubes.len()).rev() {
+        cubes.swap(index, rng.below(index as u64 + 1) as usize);
+    }
+    return (Set { cubes }, bag);
+}
+
+/// Parses a range such as `1-6`, or a single number for a fixed value. Ranges start at 1 or
+/// more, as a game without sets or a set without cubes cannot be written as a log line.
+pub fn parse_range(text: &str) -> Result<RangeInclusive<u64>, String> {
+    let (start, end) = text.split_once('-').unwrap_or((text, text));
+    let parse = |bound: &str| bound.trim().parse::<u64>().map_err(|_| format!("invalid range {:?}", text));
+    let (start, end) = (parse(start)?, parse(end)?);
+    if start > end {
+        return Err(format!("range {:?} is empty", text));
+    }
+    if start == 0 {
+        return Err(format!("range {:?} must start at 1 or more", text));
+    }
+    return Ok(start..=end);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validate::validate;
+    use crate::{is_game_possible_in, parse_games, parse_games_by_line};
+
+    fn simulation(replacement: Replacement) -> Simulation {
+        return Simulation {
+            bag: Bag::default(),
+            games: 50,
+            sets: 1..=6,
+            handful: 1..=15,
+            replacement,
+            seed: 7,
+        };
+    }
+
+    fn render(games: &[Game]) -> String {
+        return games.iter().map(|game| format!("{}\n", game)).collect();
+    }
+
+    #[test]
+    fn test_simulate_round_trips_and_is_seeded() {
+        let games = simulate(&simulation(Replacement::With));
+        assert_eq!(games.len(), 50);
+        assert_eq!(parse_games(render(&games).as_bytes()).unwrap(), games);
+        assert_eq!(simulate(&simulation(Replacement::With)), games);
+        assert_ne!(simulate(&Simulation { seed: 8, ..simulation(Replacement::With) }), games);
+    }
+
+    #[test]
+    fn test_simulated_logs_parse_and_validate() {
+        let small = Simulation {
+            bag: Bag { red: 1, green: 1, blue: 1 },
+            sets: 1..=4,
+            handful: 1..=2,
+            ..simulation(Replacement::Without)
+        };
+        for simulation in [small, simulation(Replacement::With)] {
+            let games = simulate(&simulation);
+            let parsed = parse_games_by_line(render(&games).as_bytes()).unwrap();
+            assert_eq!(parsed.iter().map(|(_, game)| game.clone()).collect::<Vec<Game>>(), games);
+            assert_eq!(validate(parsed.iter().map(|(line, game)| (*line, game))), vec![]);
+        }
+    }
+
+    #[test]
+    fn test_simulate_with_replacement() {
+        for game in simulate(&simulation(Replacement::With)) {
+            assert!(is_game_possible_in(&game, &Bag::default()), "{}", game);
+            assert!((1..=6).contains(&(game.sets.len() as u64)));
+            for set in &game.sets {
+                assert!((1..=15).contains(&set.cubes.iter().map(|cube| cube.count).sum::<u64>()), "{}", game);
+            }
+        }
+    }
+
+    #[test]
+    fn test_simulate_without_replacement() {
+        let games = simulate(&Simulation { handful: 8..=8, ..simulation(Replacement::Without) });
+        let mut emptied = 0;
+        for game in &games {
+            let drawn = game.sets.iter().flat_map(|set| &set.cubes).fold(Bag { red: 0, green: 0, blue: 0 }, |mut drawn, cube| {
+                match cube.color {
+                    RED => drawn.red += cube.count,
+                    GREEN => drawn.green += cube.count,
+                    BLUE => drawn.blue += cube.count,
+                }
+                drawn
+            });
+            assert!(drawn.red <= 12 && drawn.green <= 13 && drawn.blue <= 14, "{}", game);
+            if drawn.total() == 39 {
+                emptied += 1;
+                assert_eq!(game.sets.len(), 5, "{}", game);
+            }
+        }
+        assert!(emptied > 0);
+    }
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse_range("1-6"), Ok(1..=6));
+        assert_eq!(parse_range("4"), Ok(4..=4));
+        assert!(parse_range("6-1").is_err());
+        assert!(parse_range("0-2").is_err());
+        assert!(parse_range("0").is_err());
+        assert!(parse_range("a-b").is_err());
+    }
+}
@@ -0,0 +1,213 @@
+//! Descriptive statistics over a game log.
+
+use crate::{calc_set_power, determine_minimum_cubes, Color, Game, Set};
+use serde::Serialize;
+use std::fmt;
+
+const MAX_BINS: u64 = 20;
+const BAR_WIDTH: usize = 40;
+
+/// A histogram bin counting the values in `from..=to`.
+#[derive(PartialEq, Debug, Clone, Serialize)]
+pub struct Bin {
+    pub from: u64,
+    pub to: u64,
+    pub count: usize,
+}
+
+/// The distribution of a list of values. Histograms have a bin per value for narrow
+/// ranges and at most twenty equally wide bins otherwise.
+#[derive(PartialEq, Debug, Clone, Serialize)]
+pub struct Distribution {
+    pub count: usize,
+    pub min: u64,
+    pub max: u64,
+    pub mean: f64,
+    pub median: f64,
+    pub histogram: Vec<Bin>,
+}
+
+/// Pearson correlations between the counts of two colours in the same set, where a set
+/// without a colour counts as 0. `None` when a colour has the same count in every set.
+#[derive(PartialEq, Debug, Clone, Serialize)]
+pub struct Correlations {
+    pub red_green: Option<f64>,
+    pub red_blue: Option<f64>,
+    pub green_blue: Option<f64>,
+}
+
+#[derive(PartialEq, Debug, Clone, Serialize)]
+pub struct Stats {
+    pub games: usize,
+    pub sets: usize,
+    /// The counts of the cubes of each colour, one value per cube.
+    pub red: Distribution,
+    pub green: Distribution,
+    pub blue: Distribution,
+    pub sets_per_game: Distribution,
+    pub correlations: Correlations,
+    /// The powers of the minimum sets of the games.
+    pub power: Distribution,
+}
+
+pub fn describe(games: &[Game]) -> Stats {
+    let sets = games.iter().flat_map(|game| &game.sets).collect::<Vec<&Set>>();
+    let cube_counts = |color: Color| sets.iter()
+        .flat_map(|set| &set.cubes)
+        .filter(|cube| cube.color == color)
+        .map(|cube| cube.count)
+        .collect::<Vec<u64>>();
+    let set_counts = |color: Color| sets.iter()
+        .map(|set| set.cubes.iter().filter(|cube| cube.color == color).map(|cube| cube.count as f64).sum::<f64>())
+        .collect::<Vec<f64>>();
+    let (red, green, blue) = (set_counts(Color::RED), set_counts(Color::GREEN), set_counts(Color::BLUE));
+
+    return Stats {
+        games: games.len(),
+        sets: sets.len(),
+        red: distribution(cube_counts(Color::RED)),
+        green: distribution(cube_counts(Color::GREEN)),
+        blue: distribution(cube_counts(Color::BLUE)),
+        sets_per_game: distribution(games.iter().map(|game| game.sets.len() as u64).collect()),
+        correlations: Correlations {
+            red_green: correlation(&red, &green),
+            red_blue: correlation(&red, &blue),
+            green_blue: correlation(&green, &blue),
+        },
+        power: distribution(games.iter().map(|game| calc_set_power(&determine_minimum_cubes(game))).collect()),
+    };
+}
+
+fn distribution(mut values: Vec<u64>) -> Distribution {
+    if values.is_empty() {
+        return Distribution { count: 0, min: 0, max: 0, mean: 0.0, median: 0.0, histogram: Vec::new() };
+    }
+    values.sort_unstable();
+    let (min, max) = (values[0], values[values.len() - 1]);
+    let middle = values.len() / 2;
+    let median = if values.len().is_multiple_of(2) { (values[middle - 1] as u128 + values[middle] as u128) as f64 / 2.0 } else { values[middle] as f64 };
+
+    let width = (max - min) / MAX_BINS + 1;
+    let mut histogram = (0..=(max - min) / width)
+        .map(|bin| Bin { from: min + bin * width, to: (min + bin * width).saturating_add(width - 1), count: 0 })
+        .collect::<Vec<Bin>>();
+    for value in &values {
+        histogram[((value - min) / width) as usize].count += 1;
+    }
+
+    return Distribution {
+        count: values.len(),
+        min,
+        max,
+        mean: values.iter().map(|&value| value as u128).sum::<u128>() as f64 / values.len() as f64,
+        median,
+        histogram,
+    };
+}
+
+fn correlation(xs: &[f64], ys: &[f64]) -> Option<f64> {
+    let n = xs.len() as f64;
+    let (mean_x, mean_y) = (xs.iter().sum::<f64>() / n, ys.iter().sum::<f64>() / n);
+    let covariance = xs.iter().zip(ys).map(|(x, y)| (x - mean_x) * (y - mean_y)).sum::<f64>();
+    let variance_x = xs.iter().map(|x| (x - mean_x).powi(2)).sum::<f64>();
+    let variance_y = ys.iter().map(|y| (y - mean_y).powi(2)).sum::<f64>();
+    if variance_x == 0.0 || variance_y == 0.0 {
+        return None;
+    }
+    return Some(covariance / (variance_x * variance_y).sqrt());
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} games, {} sets", self.games, self.sets)?;
+        writeln!(f)?;
+        writeln!(f, "{:<14} {:>6} {:>6} {:>6} {:>8} {:>8}", "", "Count", "Min", "Max", "Mean", "Median")?;
+        let rows = [
+            ("Red cubes", &self.red),
+            ("Green cubes", &self.green),
+            ("Blue cubes", &self.blue),
+            ("Sets per game", &self.sets_per_game),
+            ("Power", &self.power),
+        ];
+        for (name, distribution) in rows {
+            writeln!(
+                f,
+                "{:<14} {:>6} {:>6} {:>6} {:>8.2} {:>8.1}",
+                name, distribution.count, distribution.min, distribution.max, distribution.mean, distribution.median
+            )?;
+        }
+
+        writeln!(f)?;
+        writeln!(f, "Correlation of the counts in a set")?;
+        for (name, correlation) in [
+            ("red / green", self.correlations.red_green),
+            ("red / blue", self.correlations.red_blue),
+            ("green / blue", self.correlations.green_blue),
+        ] {
+            match correlation {
+                Some(correlation) => writeln!(f, "  {:<14} {:>6.3}", name, correlation)?,
+                None => writeln!(f, "  {:<14} {:>6}", name, "-")?,
+            }
+        }
+
+        for (name, distribution) in rows {
+            writeln!(f)?;
+            writeln!(f, "{}", name)?;
+            let largest = distribution.histogram.iter().map(|bin| bin.count).max().unwrap_or(0).max(1);
+            for bin in &distribution.histogram {
+                let label = if bin.from == bin.to { bin.from.to_string() } else { format!("{}-{}", bin.from, bin.to) };
+                writeln!(f, "  {:>11} {:>5} {}", label, bin.count, "#".repeat(bin.count * BAR_WIDTH / largest))?;
+            }
+        }
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_games;
+
+    #[test]
+    fn test_describe() {
+        let stats = describe(&parse_games(include_bytes!("test-input.txt")).unwrap());
+        assert_eq!((stats.games, stats.sets), (5, 14));
+        assert_eq!(
+            stats.sets_per_game,
+            Distribution {
+                count: 5,
+                min: 2,
+                max: 3,
+                mean: 2.8,
+                median: 3.0,
+                histogram: vec![Bin { from: 2, to: 2, count: 1 }, Bin { from: 3, to: 3, count: 4 }],
+            }
+        );
+        assert_eq!((stats.red.count, stats.red.min, stats.red.max, stats.red.median), (11, 1, 20, 4.0));
+        assert_eq!(stats.power.histogram.len(), 20);
+        assert_eq!(stats.power.histogram[0], Bin { from: 12, to: 89, count: 3 });
+        assert_eq!(stats.power.histogram[19], Bin { from: 1494, to: 1571, count: 1 });
+        assert!(stats.correlations.red_green.unwrap() > 0.0);
+    }
+
+    #[test]
+    fn test_describe_constant_counts() {
+        let stats = describe(&parse_games(b"Game 1: 2 red, 1 blue; 2 red, 3 blue").unwrap());
+        assert_eq!(stats.correlations.red_blue, None);
+        assert_eq!(stats.green.count, 0);
+        assert_eq!(stats.blue.median, 2.0);
+        assert!(stats.to_string().contains("red / blue          -"));
+    }
+
+    #[test]
+    fn test_describe_large_counts() {
+        let stats = describe(&parse_games(b"Game 1: 18446744073709551615 red, 1 red; 18446744073709551613 red").unwrap());
+        assert_eq!(stats.red.median, 18446744073709551613.0);
+        assert_eq!(stats.red.mean, (2.0 * 18446744073709551614.0 + 1.0) / 3.0);
+        assert_eq!(stats.red.histogram.last().unwrap().to, u64::MAX);
+        assert_eq!(stats.red.histogram.iter().map(|bin| bin.count).sum::<usize>(), 3);
+
+        let stats = describe(&parse_games(b"Game 1: 18446744073709551615 red; 18446744073709551613 red").unwrap());
+        assert_eq!(stats.red.median, 18446744073709551614.0);
+    }
+}
@@ -0,0 +1,63 @@
+//! Per-stage execution times, collected in thread locals and reported as averages.
+
+use std::cell::RefCell;
+use std::thread::LocalKey;
+use std::time::Instant;
+
+thread_local!(
+    pub static GLOBAL_PROCESS: RefCell<Vec<u64>> = const { RefCell::new(Vec::new()) };
+    pub static GLOBAL_LOAD_FILE_TIMES: RefCell<Vec<u64>> = const { RefCell::new(Vec::new()) };
+    pub static GLOBAL_PARSE_GAMES: RefCell<Vec<u64>> = const { RefCell::new(Vec::new()) };
+    pub static GLOBAL_FIND_POSSIBLE_GAMES: RefCell<Vec<u64>> = const { RefCell::new(Vec::new()) };
+    pub static GLOBAL_ID_SUM: RefCell<Vec<u64>> = const { RefCell::new(Vec::new()) };
+    pub static GLOBAL_DETERMINE_MINIMUM_SET: RefCell<Vec<u64>> = const { RefCell::new(Vec::new()) };
+    pub static GLOBAL_SET_POWERS: RefCell<Vec<u64>> = const { RefCell::new(Vec::new()) };
+    pub static GLOBAL_POWER_SUM: RefCell<Vec<u64>> = const { RefCell::new(Vec::new()) };
+);
+
+pub fn timed<R, F: FnOnce() -> R>(ref_cell_key: &'static LocalKey<RefCell<Vec<u64>>>, func: F) -> R {
+    let stopwatch = Instant::now();
+    let result = func();
+    ref_cell_key.with(|times| {
+        let mut borrowed_times = times.borrow_mut();
+        borrowed_times.push(stopwatch.elapsed().as_nanos() as u64)
+    });
+    return result;
+}
+
+/// Every timed stage with the name it is reported under.
+pub fn stages() -> [(&'static str, &'static LocalKey<RefCell<Vec<u64>>>); 8] {
+    return [
+        ("Overall", &GLOBAL_PROCESS),
+        ("Load File", &GLOBAL_LOAD_FILE_TIMES),
+        ("Parse Game", &GLOBAL_PARSE_GAMES),
+        ("Find Possible Games", &GLOBAL_FIND_POSSIBLE_GAMES),
+        ("Sum ID", &GLOBAL_ID_SUM),
+        ("Determine Minimum Set", &GLOBAL_DETERMINE_MINIMUM_SET),
+        ("Calculate Set Powers", &GLOBAL_SET_POWERS),
+        ("Calculate Power Sum", &GLOBAL_POWER_SUM),
+    ];
+}
+
+pub fn reset_global_times() {
+    for (_, key) in stages() {
+        key.with(|times| {
+            times.borrow_mut().clear()
+        });
+    }
+}
+
+pub fn report_global_times(prefix: String) {
+    for (name, key) in stages() {
+        key.with(|times| {
+            let borrowed_times = times.borrow();
+            println!(
+                "{} - {}: Executions {}, Time {:.2}ns",
+                prefix,
+                name,
+                borrowed_times.len(),
+                borrowed_times.iter().sum::<u64>() as f64 / borrowed_times.len() as f64
+            );
+        });
+    }
+}
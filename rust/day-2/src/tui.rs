@@ -0,0 +1,199 @@
+//! Full-screen dashboard over a game log with adjustable bag limits.
+
+use crate::{load_games, Reading};
+use day_2::{is_game_possible_in, is_set_impossible_in, summarize_in, Bag, Color, Game, Summary};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color as TerminalColor, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+use std::io;
+
+const KEYS: &str = " ↑/↓ select   r/g/b add a cube   R/G/B remove a cube   d puzzle bag   q quit";
+
+pub struct Dashboard {
+    games: Vec<Game>,
+    bag: Bag,
+    summary: Summary,
+    list_state: ListState,
+}
+
+impl Dashboard {
+    pub fn new(games: Vec<Game>) -> Dashboard {
+        let bag = Bag::default();
+        return Dashboard {
+            summary: summarize_in(&games, &bag),
+            games,
+            bag,
+            list_state: ListState::default().with_selected(Some(0)),
+        };
+    }
+
+    /// Applies a key press. Returns false once the dashboard should close.
+    pub fn handle_key(&mut self, code: KeyCode) -> bool {
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Down | KeyCode::Char('j') => self.list_state.select_next(),
+            KeyCode::Up | KeyCode::Char('k') => self.list_state.select_previous(),
+            KeyCode::Char('r') => self.bag.red += 1,
+            KeyCode::Char('g') => self.bag.green += 1,
+            KeyCode::Char('b') => self.bag.blue += 1,
+            KeyCode::Char('R') => self.bag.red = self.bag.red.saturating_sub(1),
+            KeyCode::Char('G') => self.bag.green = self.bag.green.saturating_sub(1),
+            KeyCode::Char('B') => self.bag.blue = self.bag.blue.saturating_sub(1),
+            KeyCode::Char('d') => self.bag = Bag::default(),
+            _ => {}
+        }
+        self.summary = summarize_in(&self.games, &self.bag);
+        return true;
+    }
+
+    pub fn render(&mut self, frame: &mut Frame) {
+        let [header, body, footer] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Min(1),
+            Constraint::Length(1),
+        ]).areas(frame.area());
+
+        let possible = self.games.iter().filter(|game| is_game_possible_in(game, &self.bag)).count();
+        let status = Line::from(vec![
+            Span::raw(" Bag: "),
+            Span::styled(format!("{} red", self.bag.red), Style::new().fg(terminal_color(&Color::RED))),
+            Span::raw(", "),
+            Span::styled(format!("{} green", self.bag.green), Style::new().fg(terminal_color(&Color::GREEN))),
+            Span::raw(", "),
+            Span::styled(format!("{} blue", self.bag.blue), Style::new().fg(terminal_color(&Color::BLUE))),
+            Span::raw(format!(
+                "   Possible: {}/{}   Id sum: {}   Power sum: {}",
+                possible,
+                self.games.len(),
+                self.summary.id_sum,
+                self.summary.power_sum
+            )),
+        ]);
+        frame.render_widget(Paragraph::new(status).block(Block::bordered().title(" Cube Conundrum ")), header);
+
+        let items = self.games.iter()
+            .map(|game| ListItem::new(game_line(game, &self.bag)))
+            .collect::<Vec<ListItem>>();
+        let list = List::new(items)
+            .block(Block::bordered().title(" Games "))
+            .highlight_style(Style::new().add_modifier(Modifier::BOLD))
+            .highlight_symbol("> ");
+        frame.render_stateful_widget(list, body, &mut self.list_state);
+
+        frame.render_widget(Line::styled(KEYS, Style::new().add_modifier(Modifier::DIM)), footer);
+    }
+
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        loop {
+            terminal.draw(|frame| self.render(frame))?;
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press && !self.handle_key(key.code) {
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
+
+// Cubes are drawn in their colour; sets the bag cannot produce are shown reversed.
+fn game_line(game: &Game, bag: &Bag) -> Line<'static> {
+    let (marker, marker_color) = if is_game_possible_in(game, bag) { ("✓ ", TerminalColor::Green) } else { ("✗ ", TerminalColor::Red) };
+    let mut spans = vec![
+        Span::styled(marker, Style::new().fg(marker_color)),
+        Span::raw(format!("Game {}: ", game.number)),
+    ];
+    for (set_index, set) in game.sets.iter().enumerate() {
+        if set_index > 0 {
+            spans.push(Span::raw("; "));
+        }
+        let set_style = if is_set_impossible_in(set, bag) { Style::new().add_modifier(Modifier::REVERSED) } else { Style::new() };
+        for (cube_index, cube) in set.cubes.iter().enumerate() {
+            if cube_index > 0 {
+                spans.push(Span::styled(", ", set_style));
+            }
+            spans.push(Span::styled(cube.to_string(), set_style.fg(terminal_color(&cube.color))));
+        }
+    }
+    return Line::from(spans);
+}
+
+fn terminal_color(color: &Color) -> TerminalColor {
+    return match color {
+        Color::RED => TerminalColor::Red,
+        Color::GREEN => TerminalColor::Green,
+        Color::BLUE => TerminalColor::Blue,
+    };
+}
+
+pub fn run(input: &str, reading: Reading) {
+    let mut dashboard = Dashboard::new(load_games(input, reading));
+    let mut terminal = ratatui::init();
+    let result = dashboard.run(&mut terminal);
+    ratatui::restore();
+    if let Err(error) = result {
+        eprintln!("{}", error);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use day_2::parse_games;
+    use day_2::timing::stages;
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+
+    fn test_dashboard() -> Dashboard {
+        return Dashboard::new(parse_games(include_bytes!("test-input.txt")).unwrap());
+    }
+
+    fn rendered(dashboard: &mut Dashboard) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(100, 12)).unwrap();
+        terminal.draw(|frame| dashboard.render(frame)).unwrap();
+        return terminal.backend().buffer().content().iter().map(|cell| cell.symbol()).collect();
+    }
+
+    #[test]
+    fn test_renders_sums_and_games() {
+        let screen = rendered(&mut test_dashboard());
+        assert!(screen.contains("Possible: 3/5   Id sum: 8   Power sum: 2286"));
+        assert!(screen.contains("✗ Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red"));
+    }
+
+    #[test]
+    fn test_limits_follow_keys() {
+        let mut dashboard = test_dashboard();
+        for _ in 0..8 {
+            assert!(dashboard.handle_key(KeyCode::Char('r')));
+        }
+        assert!(dashboard.handle_key(KeyCode::Char('b')));
+        assert!(rendered(&mut dashboard).contains("Bag: 20 red, 13 green, 15 blue   Possible: 5/5   Id sum: 15"));
+
+        dashboard.handle_key(KeyCode::Char('d'));
+        dashboard.handle_key(KeyCode::Char('G'));
+        assert!(rendered(&mut dashboard).contains("Bag: 12 red, 12 green, 14 blue   Possible: 3/5   Id sum: 8"));
+        assert!(!dashboard.handle_key(KeyCode::Char('q')));
+    }
+
+    #[test]
+    fn test_key_presses_keep_no_times() {
+        let mut dashboard = test_dashboard();
+        for _ in 0..100 {
+            dashboard.handle_key(KeyCode::Char('r'));
+        }
+        for (name, key) in stages() {
+            assert_eq!(key.with(|times| times.borrow().len()), 0, "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_impossible_sets_are_reversed() {
+        let game = parse_games(b"Game 1: 3 blue; 20 red").unwrap().remove(0);
+        let line = game_line(&game, &Bag::default());
+        let modifiers = line.spans.iter().map(|span| span.style.add_modifier.contains(Modifier::REVERSED)).collect::<Vec<bool>>();
+        assert_eq!(modifiers, vec![false, false, false, false, true]);
+    }
+}
@@ -0,0 +1,265 @@
+//! Checks the invariants a game log should satisfy but the parser does not enforce.
+//!
+//! Issues are errors when they change the answers: a colour listed twice in one set is
+//! judged as two separate handfuls, and a repeated game id is added to the id sum twice.
+//! A colour whose counts in a set add up past `u64::MAX` is an error too, since it cannot be
+//! merged. Ids out of sequence, empty sets and cubes with a count of zero are warnings.
+
+use crate::{Color, Cube, Game, Set};
+use std::collections::HashMap;
+use std::fmt;
+
+#[derive(PartialEq, Debug, Clone, Copy, PartialOrd, Ord, Eq)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub enum IssueKind {
+    DuplicateColor { set: usize, color: Color },
+    /// The counts of a colour listed more than once in a set add up past `u64::MAX`.
+    CountOverflow { set: usize, color: Color },
+    DuplicateId { first_line: usize },
+    NonSequentialId { expected: u64 },
+    EmptySet { set: usize },
+    ZeroCount { set: usize, color: Color },
+}
+
+/// An issue of the game starting on `line` of the log, counted from 1 like the sets.
+#[derive(PartialEq, Debug, Clone)]
+pub struct Issue {
+    pub line: usize,
+    pub game: u64,
+    pub kind: IssueKind,
+}
+
+/// What to do with a log that has issues.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum Policy {
+    /// Reports the issues and keeps the games as they are.
+    Warn,
+    /// Adds up duplicate colours within a set and folds the sets of a repeated game id into
+    /// its first game, then reports the remaining issues. Refuses logs whose counts cannot
+    /// be added up.
+    Merge,
+    /// Refuses logs with errors.
+    Reject,
+}
+
+impl Issue {
+    pub fn severity(&self) -> Severity {
+        return match self.kind {
+            IssueKind::DuplicateColor { .. } | IssueKind::CountOverflow { .. } | IssueKind::DuplicateId { .. } => Severity::Error,
+            _ => Severity::Warning,
+        };
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        };
+    }
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}: game {} ", self.line, self.severity(), self.game)?;
+        return match &self.kind {
+            IssueKind::DuplicateColor { set, color } => write!(f, "set {} lists {} more than once", set, color),
+            IssueKind::CountOverflow { set, color } => write!(f, "set {} lists more {} than a count can hold", set, color),
+            IssueKind::DuplicateId { first_line } => write!(f, "was already listed on line {}", first_line),
+            IssueKind::NonSequentialId { expected } => write!(f, "is out of sequence, expected game {}", expected),
+            IssueKind::EmptySet { set } => write!(f, "set {} is empty", set),
+            IssueKind::ZeroCount { set, color } => write!(f, "set {} has 0 {}", set, color),
+        };
+    }
+}
+
+/// Parses a policy name: `warn`, `merge` or `reject`.
+pub fn parse_policy(text: &str) -> Result<Policy, String> {
+    return match text {
+        "warn" => Ok(Policy::Warn),
+        "merge" => Ok(Policy::Merge),
+        "reject" => Ok(Policy::Reject),
+        _ => Err(format!("unknown policy {:?}, expected warn, merge or reject", text)),
+    };
+}
+
+/// Every issue of a log given as its games paired with the line each starts on, in log order.
+pub fn validate<'a>(games: impl IntoIterator<Item = (usize, &'a Game)>) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    let mut first_lines = HashMap::new();
+    let mut expected = 1;
+    for (line, game) in games {
+        let issue = |kind| Issue { line, game: game.number, kind };
+        match first_lines.get(&game.number) {
+            Some(&first_line) => issues.push(issue(IssueKind::DuplicateId { first_line })),
+            None if game.number != expected => issues.push(issue(IssueKind::NonSequentialId { expected })),
+            None => {}
+        }
+        first_lines.entry(game.number).or_insert(line);
+        expected = game.number.saturating_add(1);
+
+        for (set_index, set) in game.sets.iter().enumerate() {
+            let set_number = set_index + 1;
+            if set.cubes.is_empty() {
+                issues.push(issue(IssueKind::EmptySet { set: set_number }));
+            }
+            let mut seen: Vec<(Color, Option<u64>)> = Vec::new();
+            for cube in &set.cubes {
+                if cube.count == 0 {
+                    issues.push(issue(IssueKind::ZeroCount { set: set_number, color: cube.color.clone() }));
+                }
+                match seen.iter_mut().find(|(color, _)| *color == cube.color) {
+                    Some((_, total)) => {
+                        issues.push(issue(IssueKind::DuplicateColor { set: set_number, color: cube.color.clone() }));
+                        let sum = total.and_then(|total| total.checked_add(cube.count));
+                        if total.is_some() && sum.is_none() {
+                            issues.push(issue(IssueKind::CountOverflow { set: set_number, color: cube.color.clone() }));
+                        }
+                        *total = sum;
+                    }
+                    None => seen.push((cube.color.clone(), Some(cube.count))),
+                }
+            }
+        }
+    }
+    return issues;
+}
+
+/// Applies `policy` to a parsed log of games paired with their lines. Returns the games to
+/// solve together with the issues left to report, or every issue if the log is rejected.
+pub fn apply_policy(games: Vec<(usize, Game)>, policy: Policy) -> Result<(Vec<Game>, Vec<Issue>), Vec<Issue>> {
+    let issues = validate(games.iter().map(|(line, game)| (*line, game)));
+    let games = games.into_iter().map(|(_, game)| game).collect();
+    return match policy {
+        Policy::Warn => Ok((games, issues)),
+        Policy::Reject if issues.iter().any(|issue| issue.severity() == Severity::Error) => Err(issues),
+        Policy::Reject => Ok((games, issues)),
+        Policy::Merge if issues.iter().any(|issue| matches!(issue.kind, IssueKind::CountOverflow { .. })) => Err(issues),
+        Policy::Merge => {
+            let remaining = issues.into_iter()
+                .filter(|issue| !matches!(issue.kind, IssueKind::DuplicateColor { .. } | IssueKind::DuplicateId { .. }))
+                .collect();
+            Ok((merge_duplicates(games), remaining))
+        }
+    };
+}
+
+/// Adds up the counts of a colour listed more than once in a set, and appends the sets of
+/// a repeated game id to the first game with that id. Counts that would overflow stop at
+/// `u64::MAX`, which [`validate`] reports as [`IssueKind::CountOverflow`].
+pub fn merge_duplicates(games: Vec<Game>) -> Vec<Game> {
+    let mut merged: Vec<Game> = Vec::new();
+    let mut positions: HashMap<u64, usize> = HashMap::new();
+    for game in games {
+        let sets = game.sets.into_iter().map(|set| {
+            let mut cubes: Vec<Cube> = Vec::new();
+            for cube in set.cubes {
+                match cubes.iter_mut().find(|existing| existing.color == cube.color) {
+                    Some(existing) => existing.count = existing.count.saturating_add(cube.count),
+                    None => cubes.push(cube),
+                }
+            }
+            Set { cubes }
+        });
+        match positions.get(&game.number) {
+            Some(&position) => merged[position].sets.extend(sets),
+            None => {
+                positions.insert(game.number, merged.len());
+                merged.push(Game { number: game.number, sets: sets.collect() });
+            }
+        }
+    }
+    return merged;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::import::{parse_games_by_line_as, InputFormat};
+    use aoc::normalize::InvalidUtf8;
+    use crate::{parse_games, parse_games_by_line, summarize};
+
+    fn validate_log(games: &[(usize, Game)]) -> Vec<Issue> {
+        return validate(games.iter().map(|(line, game)| (*line, game)));
+    }
+
+    const LOG: &[u8] = b"Game 1: 3 red, 2 red; 1 blue\nGame 2: 0 green, 1 blue\nGame 4: 5 red\nGame 2: 13 red\n";
+
+    #[test]
+    fn test_validate() {
+        let issues = validate_log(&parse_games_by_line(LOG).unwrap());
+        assert_eq!(
+            issues.iter().map(|issue| issue.to_string()).collect::<Vec<String>>(),
+            vec![
+                "line 1: error: game 1 set 1 lists red more than once",
+                "line 2: warning: game 2 set 1 has 0 green",
+                "line 3: warning: game 4 is out of sequence, expected game 3",
+                "line 4: error: game 2 was already listed on line 2",
+            ]
+        );
+        assert_eq!(validate_log(&parse_games_by_line(include_bytes!("test-input.txt")).unwrap()), vec![]);
+
+        let games = parse_games_by_line_as(b"[\n{\"id\": 1, \"sets\": [{\"red\": 1}, {}]}]", InputFormat::Json, InvalidUtf8::Report).unwrap();
+        assert_eq!(validate_log(&games), vec![Issue { line: 2, game: 1, kind: IssueKind::EmptySet { set: 2 } }]);
+    }
+
+    #[test]
+    fn test_validate_reports_file_lines() {
+        let games = parse_games_by_line(b"\nGame 1: 3 red\n\n\nGame 1: 2 red, 1 red\n").unwrap();
+        assert_eq!(
+            validate_log(&games).iter().map(|issue| issue.to_string()).collect::<Vec<String>>(),
+            vec![
+                "line 5: error: game 1 was already listed on line 2",
+                "line 5: error: game 1 set 1 lists red more than once",
+            ]
+        );
+
+        let games = parse_games_by_line(b"Game 1: 3 red;\nGame 2: ; 1 blue").unwrap();
+        assert_eq!(
+            validate_log(&games),
+            vec![
+                Issue { line: 1, game: 1, kind: IssueKind::EmptySet { set: 2 } },
+                Issue { line: 2, game: 2, kind: IssueKind::EmptySet { set: 1 } },
+            ]
+        );
+
+        let games = parse_games_by_line(b"\xEF\xBB\xBF\r\nGame 1: 1 red\r\n \t\r\nGame 3: 1 red\r\n").unwrap();
+        assert_eq!(validate_log(&games), vec![Issue { line: 4, game: 3, kind: IssueKind::NonSequentialId { expected: 2 } }]);
+
+        let csv = b"game,set,color,count\n1,1,red,3\n2,1,red,1\n2,1,red,2\n";
+        let games = parse_games_by_line_as(csv, InputFormat::Csv, InvalidUtf8::Report).unwrap();
+        assert_eq!(validate_log(&games), vec![Issue { line: 3, game: 2, kind: IssueKind::DuplicateColor { set: 1, color: Color::RED } }]);
+    }
+
+    #[test]
+    fn test_policies() {
+        let games = parse_games_by_line(LOG).unwrap();
+        let (kept, issues) = apply_policy(games.clone(), Policy::Warn).unwrap();
+        assert_eq!((kept, issues.len()), (parse_games(LOG).unwrap(), 4));
+        assert_eq!(apply_policy(games.clone(), Policy::Reject).unwrap_err().len(), 4);
+
+        let (merged, issues) = apply_policy(games, Policy::Merge).unwrap();
+        assert_eq!(merged, parse_games(b"Game 1: 5 red; 1 blue\nGame 2: 0 green, 1 blue; 13 red\nGame 4: 5 red").unwrap());
+        assert_eq!(issues.len(), 2);
+        assert_eq!(summarize(&merged).id_sum, 1 + 4);
+
+        let clean = parse_games_by_line(include_bytes!("test-input.txt")).unwrap();
+        assert_eq!(apply_policy(clean, Policy::Reject), Ok((parse_games(include_bytes!("test-input.txt")).unwrap(), vec![])));
+        assert_eq!(parse_policy("merge"), Ok(Policy::Merge));
+
+        let huge = parse_games_by_line(b"Game 18446744073709551615: 18446744073709551615 red, 1 red\nGame 1: 1 red, 2 red, 3 red").unwrap();
+        let overflow = Issue { line: 1, game: u64::MAX, kind: IssueKind::CountOverflow { set: 1, color: Color::RED } };
+        let issues = validate_log(&huge);
+        assert_eq!(issues.iter().filter(|issue| issue.kind == overflow.kind).collect::<Vec<&Issue>>(), vec![&overflow]);
+        assert!(issues.contains(&Issue { line: 2, game: 1, kind: IssueKind::NonSequentialId { expected: u64::MAX } }));
+        assert!(apply_policy(huge.clone(), Policy::Merge).unwrap_err().contains(&overflow));
+        assert_eq!(merge_duplicates(huge.into_iter().map(|(_, game)| game).collect())[0].sets[0].cubes[0].count, u64::MAX);
+        assert!(parse_policy("ignore").is_err());
+    }
+}
@@ -0,0 +1,144 @@
+mod scaffold;
+mod server;
+mod watch;
+mod workspace;
+
+use aoc::answers::{parse_feedback, AnswerStore, Feedback};
+use aoc::inputs::InputManager;
+use clap::{Parser, Subcommand};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{self, Command};
+
+const YEAR: u16 = 2023;
+
+#[derive(Parser)]
+#[command(name = "aoc", about = "Scaffolds and runs the Advent of Code day crates")]
+struct Cli {
+    #[command(subcommand)]
+    command: Commands,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Generates a new day crate from the template and registers it in the workspace
+    New { day: u8 },
+    /// Runs a registered day crate in release mode, fetching its input first if it is missing
+    Run {
+        day: u8,
+        /// Re-runs the tests and the day whenever its sources or inputs change
+        #[arg(long)]
+        watch: bool,
+        /// Arguments for the day itself, after `--`, like `-- --matching greedy`
+        #[arg(last = true)]
+        args: Vec<String>,
+    },
+    /// Writes the puzzle input of a day to its src/input.txt, downloading it if it is not cached
+    Fetch {
+        day: u8,
+        #[arg(long, default_value_t = YEAR)]
+        year: u16,
+        /// Server to download from instead of AOC_BASE_URL, e.g. a local stand-in
+        #[arg(long)]
+        base_url: Option<String>,
+    },
+    /// Records the feedback an answer got when it was submitted, in the day's answers.toml
+    Answer {
+        day: u8,
+        #[arg(value_parser = clap::value_parser!(u8).range(1..=2))]
+        part: u8,
+        value: u64,
+        /// `correct`, `wrong`, `too-high` or `too-low`
+        #[arg(value_parser = parse_feedback)]
+        feedback: Feedback,
+    },
+    /// Serves the solvers over HTTP on localhost
+    Serve {
+        #[arg(long, default_value_t = 8023)]
+        port: u16,
+    },
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let root = workspace::root();
+    let result = match cli.command {
+        Commands::New { day } => scaffold::create_day(&root, day)
+            .map(|path| println!("Created {}", path.display())),
+        Commands::Run { day, watch, args } => run_day(&root, day, watch, &args),
+        Commands::Fetch { day, year, base_url } => fetch_input(&root, day, year, base_url),
+        Commands::Answer { day, part, value, feedback } => record_answer(&root, day, part, value, feedback),
+        Commands::Serve { port } => server::serve(port),
+    };
+    if let Err(error) = result {
+        eprintln!("{}", error);
+        process::exit(1);
+    }
+}
+
+fn run_day(root: &Path, day: u8, watch: bool, args: &[String]) -> io::Result<()> {
+    let name = workspace::day_name(day);
+    if !workspace::members(root)?.contains(&name) {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("{} is not registered in the workspace, create it with `aoc new {}`", name, day),
+        ));
+    }
+
+    provision_input(root, day);
+    if watch {
+        return watch::watch(root, &name, args);
+    }
+
+    // The days read their inputs relative to their own crate directory.
+    let status = Command::new("cargo")
+        .args(["run", "--release", "-p", &name, "--"])
+        .args(args)
+        .current_dir(root.join(&name))
+        .status()?;
+    if !status.success() {
+        return Err(io::Error::other(format!("{} exited with {}", name, status)));
+    }
+    return Ok(());
+}
+
+fn record_answer(root: &Path, day: u8, part: u8, value: u64, feedback: Feedback) -> io::Result<()> {
+    let name = workspace::day_name(day);
+    if !workspace::members(root)?.contains(&name) {
+        return Err(io::Error::new(io::ErrorKind::NotFound, format!("{} is not registered in the workspace", name)));
+    }
+    let path = root.join(&name).join("answers.toml");
+    let mut store = AnswerStore::load(&path)?;
+    store.record(part, value, feedback);
+    store.save(&path)?;
+    println!("Recorded {} for part {} of {} in {}", value, part, name, path.display());
+    return Ok(());
+}
+
+fn fetch_input(root: &Path, day: u8, year: u16, base_url: Option<String>) -> io::Result<()> {
+    let mut manager = InputManager::from_env();
+    if let Some(base_url) = base_url {
+        manager.base_url = base_url;
+    }
+
+    let target = input_path(root, day);
+    if manager.provision(year, day, &target)? {
+        println!("Wrote {}", target.display());
+    } else {
+        println!("{} already contains an input", target.display());
+    }
+    return Ok(());
+}
+
+fn provision_input(root: &Path, day: u8) {
+    let target = input_path(root, day);
+    match InputManager::from_env().provision(YEAR, day, &target) {
+        Ok(true) => println!("Provisioned {}", target.display()),
+        Ok(false) => {}
+        Err(error) => eprintln!("Could not provision {}: {}", target.display(), error),
+    }
+}
+
+fn input_path(root: &Path, day: u8) -> PathBuf {
+    return root.join(workspace::day_name(day)).join("src/input.txt");
+}
@@ -0,0 +1,64 @@
+use crate::workspace;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+const CARGO_TEMPLATE: &str = include_str!("../templates/Cargo.toml.template");
+const LIB_TEMPLATE: &str = include_str!("../templates/lib.rs.template");
+const MAIN_TEMPLATE: &str = include_str!("../templates/main.rs.template");
+
+/// Generates the crate for `day` inside the workspace at `root` and registers it as a member.
+pub fn create_day(root: &Path, day: u8) -> io::Result<PathBuf> {
+    let name = workspace::day_name(day);
+    let crate_path = root.join(&name);
+    if crate_path.exists() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} already exists", crate_path.display()),
+        ));
+    }
+
+    fs::create_dir_all(crate_path.join("src"))?;
+    fs::write(crate_path.join("Cargo.toml"), render(CARGO_TEMPLATE, day))?;
+    fs::write(crate_path.join("src/lib.rs"), render(LIB_TEMPLATE, day))?;
+    fs::write(crate_path.join("src/main.rs"), render(MAIN_TEMPLATE, day))?;
+    fs::write(crate_path.join("src/input.txt"), "")?;
+    fs::write(crate_path.join("src/test-input.txt"), "")?;
+    fs::write(crate_path.join("answers.toml"), "")?;
+
+    workspace::register_member(root, &name)?;
+    return Ok(crate_path);
+}
+
+fn render(template: &str, day: u8) -> String {
+    return template
+        .replace("{{day}}", &workspace::day_name(day))
+        .replace("{{crate}}", &workspace::day_name(day).replace('-', "_"))
+        .replace("{{number}}", &day.to_string());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_day() {
+        let root = std::env::temp_dir().join(format!("aoc-scaffold-{}", std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join("Cargo.toml"), "[workspace]\nmembers = [\n    \"aoc\",\n]\n").unwrap();
+
+        let crate_path = create_day(&root, 3).unwrap();
+
+        assert!(crate_path.join("src/main.rs").exists());
+        assert!(fs::read_to_string(crate_path.join("src/main.rs")).unwrap().starts_with("use day_3::"));
+        assert!(crate_path.join("src/test-input.txt").exists());
+        assert!(fs::read_to_string(crate_path.join("Cargo.toml")).unwrap().contains("name = \"day-3\""));
+        assert_eq!(
+            workspace::members(&root).unwrap(),
+            vec!["aoc".to_string(), "day-3".to_string()]
+        );
+        assert_eq!(create_day(&root, 3).unwrap_err().kind(), io::ErrorKind::AlreadyExists);
+
+        fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,163 @@
+//! A localhost HTTP API over the day solvers.
+//!
+//! Every endpoint takes the raw puzzle input as the request body and answers with JSON:
+//!
+//! - `POST /day/1/calibrate?matching=greedy` returns the calibration value. `matching` is
+//!   `overlapping` as in the puzzle when left out.
+//! - `POST /day/2/evaluate?red=12&green=13&blue=14` returns the id sum and power sum for the
+//!   given bag. Colours that are left out keep their count from the puzzle bag.
+//!
+//! Failures are answered with an `error` message and a 4xx status.
+
+use aoc::normalize::InvalidUtf8;
+use day_1::Matching;
+use day_2::Bag;
+use serde_json::{json, Value};
+use std::io;
+use std::time::Instant;
+use tiny_http::{Header, Method, Response, Server};
+
+/// Serves the API on `127.0.0.1:port` until the process is stopped.
+pub fn serve(port: u16) -> io::Result<()> {
+    let server = Server::http(("127.0.0.1", port)).map_err(io::Error::other)?;
+    println!("Listening on http://127.0.0.1:{}", port);
+    let content_type = Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]).unwrap();
+    for mut request in server.incoming_requests() {
+        let mut body = Vec::new();
+        let (status, value) = match request.as_reader().read_to_end(&mut body) {
+            Ok(_) => handle(request.method(), request.url(), &body),
+            Err(error) => (400, error_body(format!("could not read the request body: {}", error))),
+        };
+        let response = Response::from_string(value.to_string())
+            .with_status_code(status)
+            .with_header(content_type.clone());
+        if let Err(error) = request.respond(response) {
+            eprintln!("Could not respond: {}", error);
+        }
+    }
+    return Ok(());
+}
+
+/// Routes a request and returns the status code and JSON body of the response.
+pub fn handle(method: &Method, url: &str, body: &[u8]) -> (u16, Value) {
+    let (path, query) = url.split_once('?').unwrap_or((url, ""));
+    let result = match path {
+        "/day/1/calibrate" | "/day/2/evaluate" if *method != Method::Post => {
+            return (405, error_body(format!("{} only accepts POST", path)));
+        }
+        "/day/1/calibrate" => parse_matching(query).and_then(|matching| calibrate(body, matching)),
+        "/day/2/evaluate" => parse_bag(query).and_then(|bag| evaluate(body, &bag)),
+        _ => return (404, error_body(format!("no endpoint at {}", path))),
+    };
+    return match result {
+        Ok(value) => (200, value),
+        Err(message) => (400, error_body(message)),
+    };
+}
+
+fn calibrate(body: &[u8], matching: Matching) -> Result<Value, String> {
+    let scanner = day_1::Scanner::detect().with_matching(matching);
+    let stopwatch = Instant::now();
+    let number = day_1::calibrate(body, scanner, InvalidUtf8::Report).map_err(|error| error.to_string())?;
+    let elapsed = stopwatch.elapsed().as_nanos() as u64;
+    return Ok(json!({
+        "value": number.value,
+        "scanner": scanner.name(),
+        "timings_ns": { "Overall": elapsed },
+    }));
+}
+
+fn evaluate(body: &[u8], bag: &Bag) -> Result<Value, String> {
+    let stopwatch = Instant::now();
+    let games = day_2::parse_games(body).map_err(|error| error.to_string())?;
+    let parsed = stopwatch.elapsed().as_nanos() as u64;
+    let summary = day_2::summarize_in(&games, bag);
+    let elapsed = stopwatch.elapsed().as_nanos() as u64;
+    let timings = json!({ "Overall": elapsed, "Parse Game": parsed, "Summarize": elapsed - parsed });
+    return Ok(json!({
+        "id_sum": summary.id_sum,
+        "power_sum": summary.power_sum,
+        "bag": { "red": bag.red, "green": bag.green, "blue": bag.blue },
+        "timings_ns": timings,
+    }));
+}
+
+fn parse_matching(query: &str) -> Result<Matching, String> {
+    let mut matching = Matching::Overlapping;
+    for parameter in query.split('&').filter(|parameter| !parameter.is_empty()) {
+        match parameter.split_once('=') {
+            Some(("matching", value)) => matching = day_1::parse_matching(value)?,
+            _ => return Err(format!("unknown parameter {:?}", parameter)),
+        }
+    }
+    return Ok(matching);
+}
+
+fn parse_bag(query: &str) -> Result<Bag, String> {
+    let mut bag = Bag::default();
+    for parameter in query.split('&').filter(|parameter| !parameter.is_empty()) {
+        let (name, value) = parameter.split_once('=').unwrap_or((parameter, ""));
+        let count = value
+            .parse::<u64>()
+            .map_err(|_| format!("invalid cube count {:?} for {}", value, name))?;
+        match name {
+            "red" => bag.red = count,
+            "green" => bag.green = count,
+            "blue" => bag.blue = count,
+            _ => return Err(format!("unknown parameter {:?}", name)),
+        }
+    }
+    return Ok(bag);
+}
+
+fn error_body(message: String) -> Value {
+    return json!({ "error": message });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GAMES: &[u8] = b"Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green\n\
+        Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue\n\
+        Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red\n";
+
+    #[test]
+    fn test_calibrate() {
+        let (status, value) = handle(&Method::Post, "/day/1/calibrate", b"two1nine\neightwothree\n");
+        assert_eq!(status, 200);
+        assert_eq!(value["value"], 29 + 83);
+        assert!(value["timings_ns"]["Overall"].is_u64());
+
+        let (_, value) = handle(&Method::Post, "/day/1/calibrate?matching=greedy", b"oneight\n");
+        assert_eq!(value["value"], 11);
+        assert_eq!(handle(&Method::Post, "/day/1/calibrate?matching=nope", b"oneight\n").0, 400);
+
+        let (status, value) = handle(&Method::Post, "/day/1/calibrate", b"two1nine\nnothing\n");
+        assert_eq!((status, value), (400, json!({ "error": "line 2: line contains no digits" })));
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let (status, value) = handle(&Method::Post, "/day/2/evaluate", GAMES);
+        assert_eq!(status, 200);
+        assert_eq!(value["id_sum"], 3);
+        assert_eq!(value["power_sum"], 48 + 12 + 1560);
+        assert!(value["timings_ns"]["Parse Game"].is_u64());
+        assert!(value["timings_ns"].get("Load File").is_none());
+
+        let (_, value) = handle(&Method::Post, "/day/2/evaluate?red=20&blue=14", GAMES);
+        assert_eq!(value["id_sum"], 6);
+        assert_eq!(value["bag"], json!({ "red": 20, "green": 13, "blue": 14 }));
+    }
+
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(handle(&Method::Post, "/day/2/evaluate?red=many", GAMES).0, 400);
+        assert_eq!(handle(&Method::Post, "/day/2/evaluate?purple=1", GAMES).0, 400);
+        assert_eq!(handle(&Method::Post, "/day/2/evaluate", b"Game 1 3 blue\n").0, 400);
+        assert_eq!(handle(&Method::Get, "/day/2/evaluate", GAMES).0, 405);
+        assert_eq!(handle(&Method::Post, "/day/3/solve", GAMES).0, 404);
+    }
+}
@@ -0,0 +1,247 @@
+//! Re-runs a day whenever its sources or inputs change and compares the output with the
+//! previous run.
+
+use notify::{EventKind, RecursiveMode, Watcher};
+use std::io;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::time::Duration;
+
+// Editors tend to write a file in several steps, those events are folded into one run.
+const SETTLE: Duration = Duration::from_millis(200);
+const WATCHED_EXTENSIONS: [&str; 2] = ["rs", "txt"];
+
+/// A line of day output, split at its last `": "` into a label and a value.
+#[derive(PartialEq, Debug, Clone)]
+pub struct Entry {
+    pub label: String,
+    pub value: Value,
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub enum Value {
+    /// A duration in nanoseconds, from values such as `Time 165255.00ns` or `4.6ms`.
+    Time(f64),
+    Text(String),
+}
+
+/// Watches `day-N/src` of the workspace until interrupted, running the day with `args`.
+pub fn watch(root: &Path, name: &str, args: &[String]) -> io::Result<()> {
+    let sources = root.join(name).join("src");
+    let (sender, receiver) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(sender).map_err(io::Error::other)?;
+    watcher
+        .watch(&sources, RecursiveMode::Recursive)
+        .map_err(io::Error::other)?;
+
+    let mut previous = run_once(root, name, args)?;
+    println!("Watching {} for changes", sources.display());
+    while let Ok(event) = receiver.recv() {
+        if !is_relevant(&event.map_err(io::Error::other)?) {
+            continue;
+        }
+        while receiver.recv_timeout(SETTLE).is_ok() {}
+
+        println!();
+        let current = run_once(root, name, args)?;
+        let changes = diff(&previous, &current);
+        if changes.is_empty() {
+            println!("No changes since the previous run");
+        } else {
+            println!("Changes since the previous run:");
+            for change in changes {
+                println!("  {}", change);
+            }
+        }
+        previous = current;
+        println!("Watching {} for changes", sources.display());
+    }
+    return Ok(());
+}
+
+fn is_relevant(event: &notify::Event) -> bool {
+    if matches!(event.kind, EventKind::Access(_)) {
+        return false;
+    }
+    return event.paths.iter().any(|path| {
+        path.extension()
+            .and_then(|extension| extension.to_str())
+            .is_some_and(|extension| WATCHED_EXTENSIONS.contains(&extension))
+    });
+}
+
+// Runs the tests and then the day itself, returning the entries the day printed.
+fn run_once(root: &Path, name: &str, args: &[String]) -> io::Result<Vec<Entry>> {
+    let tests = Command::new("cargo")
+        .args(["test", "-q", "-p", name])
+        .current_dir(root.join(name))
+        .output()?;
+    if tests.status.success() {
+        println!("Tests passed");
+    } else {
+        print!("{}", String::from_utf8_lossy(&tests.stdout));
+        eprint!("{}", String::from_utf8_lossy(&tests.stderr));
+        println!("Tests failed");
+    }
+
+    let run = Command::new("cargo")
+        .args(["run", "-q", "--release", "-p", name, "--"])
+        .args(args)
+        .current_dir(root.join(name))
+        .stderr(Stdio::inherit())
+        .output()?;
+    let output = String::from_utf8_lossy(&run.stdout);
+    print!("{}", output);
+    if !run.status.success() {
+        println!("{} exited with {}", name, run.status);
+    }
+    return Ok(parse_output(&output));
+}
+
+/// Splits the output of a day into labelled entries, skipping lines without a value.
+pub fn parse_output(output: &str) -> Vec<Entry> {
+    return output
+        .lines()
+        .filter_map(|line| line.rsplit_once(": "))
+        .map(|(label, value)| Entry {
+            label: label.trim().to_string(),
+            value: parse_value(value.trim()),
+        })
+        .collect();
+}
+
+fn parse_value(value: &str) -> Value {
+    let time = value.rsplit(' ').next().unwrap_or(value);
+    for (unit, factor) in [("ns", 1.0), ("µs", 1e3), ("ms", 1e6), ("s", 1e9)] {
+        if let Some(Ok(amount)) = time.strip_suffix(unit).map(|amount| amount.parse::<f64>()) {
+            return Value::Time(amount * factor);
+        }
+    }
+    return Value::Text(value.to_string());
+}
+
+/// Describes what changed between two runs: new, removed and changed answers, and every
+/// timing together with its relative change. An entry is matched with the entry of the
+/// previous run that has the same label and the same number of entries with that label
+/// before it, and a label that repeats is told apart by that number, as in `Time #2`.
+pub fn diff(previous: &[Entry], current: &[Entry]) -> Vec<String> {
+    let previous = numbered(previous);
+    let current = numbered(current);
+    let mut changes = Vec::new();
+    for (label, entry) in &current {
+        let before = previous.iter().find(|(candidate, _)| candidate == label).map(|(_, before)| &before.value);
+        match (before, &entry.value) {
+            (Some(Value::Time(before)), Value::Time(after)) if *before > 0.0 => changes.push(format!(
+                "{}: {} -> {} ({:+.1}%)",
+                label,
+                format_time(*before),
+                format_time(*after),
+                (after - before) / before * 100.0
+            )),
+            (Some(before), after) if before != after => changes.push(format!(
+                "{}: {} -> {}",
+                label,
+                format_value(before),
+                format_value(after)
+            )),
+            (Some(_), _) => {}
+            (None, value) => changes.push(format!("+ {}: {}", label, format_value(value))),
+        }
+    }
+    for (label, entry) in &previous {
+        if !current.iter().any(|(candidate, _)| candidate == label) {
+            changes.push(format!("- {}: {}", label, format_value(&entry.value)));
+        }
+    }
+    return changes;
+}
+
+// Pairs every entry with its label, numbered from the second time the label appears.
+fn numbered(entries: &[Entry]) -> Vec<(String, &Entry)> {
+    return entries
+        .iter()
+        .enumerate()
+        .map(|(index, entry)| {
+            let repeats = entries[..index].iter().filter(|earlier| earlier.label == entry.label).count();
+            let label = match repeats {
+                0 => entry.label.clone(),
+                _ => format!("{} #{}", entry.label, repeats + 1),
+            };
+            (label, entry)
+        })
+        .collect();
+}
+
+fn format_value(value: &Value) -> String {
+    return match value {
+        Value::Time(nanos) => format_time(*nanos),
+        Value::Text(text) => text.clone(),
+    };
+}
+
+fn format_time(nanos: f64) -> String {
+    if nanos >= 1e6 {
+        return format!("{:.2}ms", nanos / 1e6);
+    }
+    if nanos >= 1e3 {
+        return format!("{:.2}µs", nanos / 1e3);
+    }
+    return format!("{:.0}ns", nanos);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_output() {
+        let output = "Process Input - Load File: Executions 1, Time 165255.00ns\n\
+            Official average input: 4.6ms\n\
+            Sum of possible games: 2486\n\
+            Skipping large input benchmark\n";
+        assert_eq!(
+            parse_output(output),
+            vec![
+                Entry { label: "Process Input - Load File".to_string(), value: Value::Time(165255.0) },
+                Entry { label: "Official average input".to_string(), value: Value::Time(4600000.0) },
+                Entry { label: "Sum of possible games".to_string(), value: Value::Text("2486".to_string()) },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff() {
+        let previous = parse_output(
+            "Load File: Executions 1, Time 2000.00ns\nSum of possible games: 2486\nPart 1 answer 2486: correct\nPower Sum of games: 87984\n",
+        );
+        let current = parse_output(
+            "Load File: Executions 1, Time 1000.00ns\nSum of possible games: 2480\nPart 1 answer 2480: too low\nPower Sum of games: 87984\n",
+        );
+        assert_eq!(
+            diff(&previous, &current),
+            vec![
+                "Load File: 2.00µs -> 1.00µs (-50.0%)".to_string(),
+                "Sum of possible games: 2486 -> 2480".to_string(),
+                "+ Part 1 answer 2480: too low".to_string(),
+                "- Part 1 answer 2486: correct".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_repeated_labels_and_zero_times() {
+        let previous = parse_output("Time: 0ns\nTime: 2000ns\nAnswer: 1\nAnswer: 2\n");
+        let current = parse_output("Time: 5ns\nTime: 1000ns\nAnswer: 1\nAnswer: 3\nAnswer: 4\n");
+        assert_eq!(
+            diff(&previous, &current),
+            vec![
+                "Time: 0ns -> 5ns".to_string(),
+                "Time #2: 2.00µs -> 1.00µs (-50.0%)".to_string(),
+                "Answer #2: 2 -> 3".to_string(),
+                "+ Answer #3: 4".to_string(),
+            ]
+        );
+        assert_eq!(diff(&current, &previous).last(), Some(&"- Answer #3: 4".to_string()));
+    }
+}
@@ -0,0 +1,95 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+const MANIFEST: &str = "Cargo.toml";
+
+/// The Rust workspace the runner was built from.
+pub fn root() -> PathBuf {
+    return Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .unwrap()
+        .to_path_buf();
+}
+
+pub fn day_name(day: u8) -> String {
+    return format!("day-{}", day);
+}
+
+/// Returns the members listed in the workspace manifest.
+pub fn members(root: &Path) -> io::Result<Vec<String>> {
+    let manifest = fs::read_to_string(root.join(MANIFEST))?;
+    return parse_members(&manifest);
+}
+
+/// Appends `member` to the workspace members so cargo and the runner pick it up.
+pub fn register_member(root: &Path, member: &str) -> io::Result<()> {
+    let path = root.join(MANIFEST);
+    let manifest = fs::read_to_string(&path)?;
+    let mut members = parse_members(&manifest)?;
+    if members.iter().any(|existing| existing == member) {
+        return Ok(());
+    }
+    members.push(member.to_string());
+    return fs::write(path, render_members(&manifest, &members)?);
+}
+
+fn parse_members(manifest: &str) -> io::Result<Vec<String>> {
+    let value = manifest
+        .parse::<toml::Table>()
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+    let members = value
+        .get("workspace")
+        .and_then(|workspace| workspace.get("members"))
+        .and_then(|members| members.as_array())
+        .ok_or_else(|| invalid_data("workspace manifest has no members list"))?;
+    return Ok(members
+        .iter()
+        .filter_map(|member| member.as_str())
+        .map(|member| member.to_string())
+        .collect());
+}
+
+fn render_members(manifest: &str, members: &[String]) -> io::Result<String> {
+    let start = manifest
+        .find("members = [")
+        .ok_or_else(|| invalid_data("workspace manifest has no members list"))?;
+    let end = start + manifest[start..]
+        .find(']')
+        .ok_or_else(|| invalid_data("unterminated members list"))?;
+
+    let mut rendered = "members = [\n".to_string();
+    for member in members {
+        rendered.push_str(&format!("    \"{}\",\n", member));
+    }
+    rendered.push(']');
+    return Ok(format!("{}{}{}", &manifest[..start], rendered, &manifest[end + 1..]));
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    return io::Error::new(io::ErrorKind::InvalidData, message.to_string());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MANIFEST_CONTENT: &str = "[workspace]\nresolver = \"2\"\nmembers = [\n    \"aoc\",\n    \"day-1\",\n]\n\n[workspace.lints.clippy]\nneedless_return = \"allow\"\n";
+
+    #[test]
+    fn test_parse_members() {
+        assert_eq!(
+            parse_members(MANIFEST_CONTENT).unwrap(),
+            vec!["aoc".to_string(), "day-1".to_string()]
+        )
+    }
+
+    #[test]
+    fn test_render_members_keeps_rest_of_manifest() {
+        let members = vec!["aoc".to_string(), "day-1".to_string(), "day-2".to_string()];
+        assert_eq!(
+            render_members(MANIFEST_CONTENT, &members).unwrap(),
+            "[workspace]\nresolver = \"2\"\nmembers = [\n    \"aoc\",\n    \"day-1\",\n    \"day-2\",\n]\n\n[workspace.lints.clippy]\nneedless_return = \"allow\"\n"
+        )
+    }
+}